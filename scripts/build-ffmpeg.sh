@@ -19,7 +19,22 @@ fi
 
 # Build
 cd $FFMPEG_DIR
-./configure --prefix="$(pwd)/build" --disable-shared --enable-static --disable-doc --disable-debug --enable-pic
+# Optional features for burning subtitles into videos:
+# - libass provides the `ass` filter and is enabled when pkg-config finds it.
+# - FFMPEG_ENABLE_GPL=1 adds the x264/x265 encoders. This makes the binary GPL-licensed, so
+#   only use it for local builds; otherwise burn-in uses the VideoToolbox encoders.
+EXTRA_FLAGS=""
+if pkg-config --exists libass 2>/dev/null; then
+  EXTRA_FLAGS="$EXTRA_FLAGS --enable-libass --enable-libfreetype"
+else
+  echo "libass not found, building ffmpeg without subtitle burn-in"
+fi
+if [ "$FFMPEG_ENABLE_GPL" = "1" ]; then
+  echo "FFMPEG_ENABLE_GPL=1: enabling libx264/libx265, the ffmpeg binary will be GPL-licensed"
+  EXTRA_FLAGS="$EXTRA_FLAGS --enable-gpl --enable-libx264 --enable-libx265"
+fi
+./configure --prefix="$(pwd)/build" --disable-shared --enable-static --disable-doc --disable-debug --enable-pic \
+  $EXTRA_FLAGS
 make -j$(sysctl -n hw.ncpu)
 make install
 cd ..
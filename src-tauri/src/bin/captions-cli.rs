@@ -0,0 +1,6 @@
+//! Headless command-line entrypoint for scripted transcription and subtitle conversion.
+
+/// Main entrypoint. Runs the CLI and exits with its status code.
+fn main() {
+    std::process::exit(captions_lib::run_cli())
+}
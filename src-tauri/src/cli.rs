@@ -0,0 +1,610 @@
+//! Headless command-line interface for scripted transcription, model management and
+//! subtitle conversion, sharing the same pipeline as the desktop app.
+
+use crate::context::PlainContext;
+use crate::decoding::get_decoding_preset;
+use crate::ffmpeg::{burn_subtitles_job, extract_subtitle_stream, AudioFilters, BurnOptions};
+use crate::models::{delete_model, fetch_model, installed_models, remote_models};
+use crate::probe::probe;
+use crate::styles::get_style_preset;
+use crate::subtitles::{SubtitleDocument, VttOptions};
+use crate::vad::VadOptions;
+use crate::whisper::{
+    document_from_json, transcribe_job, ChunkingOptions, TranscriptText, TranscriptionOptions,
+    WhisperOptions, WhisperTask,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Same identifier as `tauri.conf.json`, so the CLI shares models and presets with the app.
+const APP_IDENTIFIER: &str = "tech.ziasvannes.captions";
+
+const USAGE: &str = "Usage: captions-cli [global options] <command>
+
+Commands:
+  transcribe <input> --model <file> [--language <code>] [--format <fmt>]
+             [--output <path>] [--word-timestamps] [--style <preset>]
+             [--audio-track <n[,n...]>] [--audio-language <code>] [--mix-audio]
+             [--start <time>] [--end <time>] [--normalize] [--denoise]
+             [--highpass <hz>] [--lowpass <hz>] [--gain <db>]
+             [--workers <n>] [--threads <n>] [--chunk <time>]
+             [--vad] [--vad-threshold <db>] [--detect-language]
+             [--task transcribe|translate] [--text original|translation|bilingual]
+             [--preset <name>] [--beam-size <n>] [--best-of <n>] [--temperature <t>]
+             [--no-fallback] [--max-len <chars>] [--split-on-word] [--prompt <text>]
+             [--suppress-nst] [--glossary <name>]
+  probe <input>
+  extract <input> [--stream <n>] [--to <fmt>] [--output <path>]
+  burn <video> --subtitles <file> --output <path> [--codec h264|h265]
+       [--quality high|balanced|small] [--style <preset>] [--karaoke]
+  convert <input> --to <fmt> [--output <path>] [--style <preset>]
+  models list [--remote]
+  models download <name>
+  models remove <name>
+
+Formats: json, srt, vtt, ass, txt. Convert reads .srt, .json (subtitle document or
+transcription) or Whisper console output. Burn reads .srt, .ass or .json; --karaoke
+needs word timings, so only works with .json. Times are seconds or [HH:]MM:SS[.mmm].
+Language auto detects the spoken language, --detect-language settles it on the first
+30 seconds before the full run.
+
+Global options:
+  --data-dir <dir>      App data directory (default: $CAPTIONS_DATA_DIR or the app's)
+  --resource-dir <dir>  Directory containing gen/whisper-bin and gen/ffmpeg-bin
+                        (default: $CAPTIONS_RESOURCE_DIR or this binary's directory)
+  --events              Print progress events as JSON lines on stderr
+  --json                Print machine-readable JSON on stdout
+  --help                Show this help
+
+Set CAPTIONS_DEBUG=1 to trace model downloads on stderr.
+
+Exit codes: 0 success, 1 failure, 2 usage error.";
+
+/// Exit code for invalid arguments.
+const EXIT_USAGE: i32 = 2;
+
+/// Options that take a value.
+const VALUE_OPTIONS: [&str; 32] = [
+    "data-dir",
+    "resource-dir",
+    "model",
+    "language",
+    "format",
+    "output",
+    "style",
+    "to",
+    "audio-track",
+    "audio-language",
+    "start",
+    "end",
+    "subtitles",
+    "codec",
+    "quality",
+    "stream",
+    "highpass",
+    "lowpass",
+    "gain",
+    "workers",
+    "threads",
+    "chunk",
+    "vad-threshold",
+    "task",
+    "text",
+    "preset",
+    "beam-size",
+    "best-of",
+    "temperature",
+    "max-len",
+    "prompt",
+    "glossary",
+];
+
+/// Options that take no value.
+const FLAG_OPTIONS: [&str; 14] = [
+    "events",
+    "json",
+    "help",
+    "word-timestamps",
+    "mix-audio",
+    "normalize",
+    "denoise",
+    "vad",
+    "detect-language",
+    "no-fallback",
+    "split-on-word",
+    "suppress-nst",
+    "karaoke",
+    "remote",
+];
+
+/// Why a command failed, which decides its exit code.
+enum CliError {
+    /// Missing, unknown or invalid arguments.
+    Usage(String),
+    /// The command itself failed.
+    Failed(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failed(message)
+    }
+}
+
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+/// Parsed command line.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Result<Self, String> {
+        let mut args = Args {
+            positional: Vec::new(),
+            options: HashMap::new(),
+            flags: HashSet::new(),
+        };
+        let mut iter = raw.into_iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if VALUE_OPTIONS.contains(&name) => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| format!("Missing value for --{}", name))?;
+                    args.options.insert(name.to_string(), value);
+                }
+                Some(name) if FLAG_OPTIONS.contains(&name) => {
+                    args.flags.insert(name.to_string());
+                }
+                Some(name) => return Err(format!("Unknown option --{}", name)),
+                None => args.positional.push(arg),
+            }
+        }
+        Ok(args)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn require(&self, name: &str) -> Result<&str, CliError> {
+        self.option(name)
+            .ok_or_else(|| usage(format!("Missing required option --{}", name)))
+    }
+
+    /// Returns the positional argument at `index`, `what` names it in the error.
+    fn positional(&self, index: usize, what: &str) -> Result<&str, CliError> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| usage(format!("Missing {}", what)))
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+/// Default app data dir, matching where Tauri puts it for the desktop app.
+fn default_data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CAPTIONS_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let base = if cfg!(target_os = "macos") {
+        home.join("Library/Application Support")
+    } else if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or(home)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share"))
+    };
+    base.join(APP_IDENTIFIER)
+}
+
+/// Default resource dir: next to the CLI binary, like the bundled app resources.
+fn default_resource_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CAPTIONS_RESOURCE_DIR") {
+        return PathBuf::from(dir);
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+        .unwrap_or_default()
+}
+
+/// Parses `90`, `1:30`, `00:01:30.500` and similar into milliseconds.
+fn parse_time_ms(value: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid time: {}", value);
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        let part: f64 = part.trim().parse().map_err(|_| invalid())?;
+        if part < 0.0 {
+            return Err(invalid());
+        }
+        seconds = seconds * 60.0 + part;
+    }
+    Ok((seconds * 1000.0).round() as u64)
+}
+
+/// Parses an optional numeric option.
+fn parse_number<T: std::str::FromStr>(args: &Args, name: &str) -> Result<Option<T>, CliError> {
+    args.option(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| usage(format!("Invalid value for --{}: {}", name, value)))
+        })
+        .transpose()
+}
+
+/// Parses an optional time option.
+fn parse_time_option(args: &Args, name: &str) -> Result<Option<u64>, CliError> {
+    args.option(name)
+        .map(|value| parse_time_ms(value).map_err(usage))
+        .transpose()
+}
+
+/// Writes command output to `--output` or stdout.
+fn write_output(args: &Args, contents: &str) -> Result<(), String> {
+    match args.option("output") {
+        Some(path) => {
+            std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+        }
+        None => {
+            println!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+/// Serializes a document in the requested subtitle format.
+fn render_document(
+    ctx: &PlainContext,
+    args: &Args,
+    document: &SubtitleDocument,
+    format: &str,
+) -> Result<String, CliError> {
+    match format {
+        "srt" => Ok(document.to_srt()),
+        "vtt" => Ok(document.to_vtt(&VttOptions {
+            word_timestamps: true,
+            ..VttOptions::default()
+        })),
+        "ass" => {
+            let style = get_style_preset(ctx, args.option("style").unwrap_or("Default"))?;
+            Ok(document.to_ass(&style, true)?)
+        }
+        "txt" => Ok(document.to_plain_text()),
+        "json" => Ok(to_json(document)?),
+        other => Err(usage(format!("Unsupported format: {}", other))),
+    }
+}
+
+fn cmd_transcribe(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let input = args.positional(1, "input file for transcribe")?;
+    let model = args.require("model")?;
+    let language = args.option("language").unwrap_or("en");
+    let format = args.option("format").unwrap_or("srt");
+    let audio_tracks = match args.option("audio-track") {
+        Some(list) => list
+            .split(',')
+            .map(|t| {
+                t.trim()
+                    .parse()
+                    .map_err(|_| usage(format!("Invalid audio track: {}", t)))
+            })
+            .collect::<Result<Vec<u32>, CliError>>()?,
+        None => Vec::new(),
+    };
+    // Explicit decoding flags override the preset's values
+    let mut decoding = match args.option("preset") {
+        Some(name) => get_decoding_preset(ctx, name)?,
+        None => WhisperOptions::default(),
+    };
+    decoding.beam_size = parse_number(args, "beam-size")?.or(decoding.beam_size);
+    decoding.best_of = parse_number(args, "best-of")?.or(decoding.best_of);
+    decoding.temperature = parse_number(args, "temperature")?.or(decoding.temperature);
+    decoding.max_segment_length = parse_number(args, "max-len")?.or(decoding.max_segment_length);
+    decoding.threads = parse_number(args, "threads")?.or(decoding.threads);
+    if let Some(prompt) = args.option("prompt") {
+        decoding.prompt = Some(prompt.to_string());
+    }
+    decoding.no_fallback |= args.flag("no-fallback");
+    decoding.split_on_word |= args.flag("split-on-word");
+    decoding.suppress_non_speech |= args.flag("suppress-nst");
+    let options = TranscriptionOptions {
+        word_timestamps: args.flag("word-timestamps"),
+        audio_tracks,
+        audio_language: args.option("audio-language").map(str::to_string),
+        mix_audio_tracks: args.flag("mix-audio"),
+        start_ms: parse_time_option(args, "start")?,
+        end_ms: parse_time_option(args, "end")?,
+        filters: AudioFilters {
+            normalize: args.flag("normalize"),
+            highpass_hz: parse_number(args, "highpass")?,
+            lowpass_hz: parse_number(args, "lowpass")?,
+            denoise: args.flag("denoise"),
+            gain_db: parse_number(args, "gain")?,
+        },
+        chunking: ChunkingOptions {
+            workers: parse_number(args, "workers")?.unwrap_or(1),
+            chunk_ms: parse_time_option(args, "chunk")?
+                .unwrap_or(ChunkingOptions::default().chunk_ms),
+        },
+        vad: VadOptions {
+            enabled: args.flag("vad"),
+            threshold_db: parse_number(args, "vad-threshold")?,
+            ..VadOptions::default()
+        },
+        detect_language: args.flag("detect-language"),
+        task: match args.option("task") {
+            Some(task) => parse_choice("task", task)?,
+            None => WhisperTask::default(),
+        },
+        decoding,
+        glossary: args.option("glossary").map(str::to_string),
+    };
+    let text = match args.option("text") {
+        Some(text) => parse_choice("text", text)?,
+        None => TranscriptText::default(),
+    };
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let transcription = transcribe_job(ctx, &job_id, input, model, language, &options)?;
+    if let (Some(language), Some(probability)) =
+        (&transcription.language, transcription.language_probability)
+    {
+        eprintln!("Detected language: {} (p = {:.2})", language, probability);
+    }
+    let output = match format {
+        "json" => to_json(&transcription)?,
+        _ => render_document(ctx, args, &transcription.to_document(text)?, format)?,
+    };
+    Ok(write_output(args, &output)?)
+}
+
+fn cmd_convert(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let input = args.positional(1, "input file for convert")?;
+    let format = args.require("to")?;
+    let contents =
+        std::fs::read_to_string(input).map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    let lower = input.to_lowercase();
+    let document = if lower.ends_with(".srt") {
+        SubtitleDocument::from_srt(&contents)
+    } else if lower.ends_with(".json") {
+        document_from_json(&contents).map_err(|e| format!("Failed to parse {}: {}", input, e))?
+    } else {
+        SubtitleDocument::from_whisper(&contents)
+    };
+    if document.cues.is_empty() {
+        return Err(format!("No subtitle cues found in {}", input).into());
+    }
+    Ok(write_output(
+        args,
+        &render_document(ctx, args, &document, format)?,
+    )?)
+}
+
+fn cmd_probe(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let input = args.positional(1, "input file for probe")?;
+    let info = probe(ctx, input)?;
+    if args.flag("json") {
+        println!("{}", to_json(&info)?);
+        return Ok(());
+    }
+    if let Some(duration_ms) = info.duration_ms {
+        println!("Duration: {:.3}s", duration_ms as f64 / 1000.0);
+    }
+    for stream in &info.streams {
+        let kind = serde_json::to_value(&stream.kind)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let mut line = format!(
+            "#{} {}:{} {}",
+            stream.index, kind, stream.type_index, stream.codec
+        );
+        if let Some(rate) = stream.sample_rate {
+            line.push_str(&format!(" {} Hz", rate));
+        }
+        if let Some(layout) = &stream.channel_layout {
+            line.push_str(&format!(" {}", layout));
+        }
+        if let Some(language) = &stream.language {
+            line.push_str(&format!(" [{}]", language));
+        }
+        if let Some(title) = &stream.title {
+            line.push_str(&format!(" \"{}\"", title));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn cmd_extract(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let input = args.positional(1, "input file for extract")?;
+    let stream = match args.option("stream") {
+        Some(stream) => stream
+            .parse()
+            .map_err(|_| usage(format!("Invalid subtitle stream: {}", stream)))?,
+        None => 0,
+    };
+    let document = extract_subtitle_stream(ctx, input, stream)?;
+    let format = args.option("to").unwrap_or("srt");
+    Ok(write_output(
+        args,
+        &render_document(ctx, args, &document, format)?,
+    )?)
+}
+
+/// Parses a lowercase enum value the same way the Tauri commands deserialize it.
+fn parse_choice<T: serde::de::DeserializeOwned>(name: &str, value: &str) -> Result<T, CliError> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| usage(format!("Invalid value for --{}: {}", name, value)))
+}
+
+fn cmd_burn(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let video = args.positional(1, "video file for burn")?;
+    let subtitles = args.require("subtitles")?;
+    let output = args.require("output")?;
+    let mut options = BurnOptions {
+        style: args.option("style").map(str::to_string),
+        karaoke: args.flag("karaoke"),
+        ..BurnOptions::default()
+    };
+    if let Some(codec) = args.option("codec") {
+        options.codec = parse_choice("codec", codec)?;
+    }
+    if let Some(quality) = args.option("quality") {
+        options.quality = parse_choice("quality", quality)?;
+    }
+    let job_id = uuid::Uuid::new_v4().to_string();
+    Ok(burn_subtitles_job(
+        ctx, &job_id, video, subtitles, output, &options,
+    )?)
+}
+
+fn cmd_models(ctx: &PlainContext, args: &Args) -> Result<(), CliError> {
+    let json = args.flag("json");
+    match args.positional.get(1).map(String::as_str) {
+        Some("list") if args.flag("remote") => {
+            let models = remote_models(ctx);
+            if json {
+                println!("{}", to_json(&models)?);
+            } else {
+                for model in models {
+                    println!("{}\t{}\t{}", model.name, model.size, model.url);
+                }
+            }
+            Ok(())
+        }
+        Some("list") => {
+            let models = installed_models(ctx);
+            if json {
+                println!("{}", to_json(&models)?);
+            } else {
+                for model in models {
+                    println!("{}", model);
+                }
+            }
+            Ok(())
+        }
+        Some("download") => {
+            let name = args.positional(2, "model name")?;
+            Ok(tokio::runtime::Runtime::new()
+                .map_err(|e| format!("Failed to start async runtime: {}", e))?
+                .block_on(fetch_model(ctx.clone(), name.to_string()))?)
+        }
+        Some("remove") => {
+            let name = args.positional(2, "model name")?;
+            Ok(delete_model(ctx, name)?)
+        }
+        _ => Err(usage(
+            "Expected one of: models list, models download, models remove",
+        )),
+    }
+}
+
+/// Runs the CLI with the given arguments (without the program name) and returns the exit code.
+pub fn run(raw_args: Vec<String>) -> i32 {
+    let args = match Args::parse(raw_args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    if args.flag("help") || args.positional.is_empty() {
+        println!("{}", USAGE);
+        return if args.flag("help") { 0 } else { EXIT_USAGE };
+    }
+    let mut ctx = PlainContext::new(
+        args.option("resource-dir")
+            .map(PathBuf::from)
+            .unwrap_or_else(default_resource_dir),
+        args.option("data-dir")
+            .map(PathBuf::from)
+            .unwrap_or_else(default_data_dir),
+    );
+    ctx.print_events = args.flag("events");
+    let result = match args.positional[0].as_str() {
+        "transcribe" => cmd_transcribe(&ctx, &args),
+        "convert" => cmd_convert(&ctx, &args),
+        "probe" => cmd_probe(&ctx, &args),
+        "extract" => cmd_extract(&ctx, &args),
+        "burn" => cmd_burn(&ctx, &args),
+        "models" => cmd_models(&ctx, &args),
+        other => {
+            eprintln!("Unknown command: {}\n\n{}", other, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let (e, code) = match result {
+        Ok(()) => return 0,
+        Err(CliError::Usage(e)) => (e, EXIT_USAGE),
+        Err(CliError::Failed(e)) => (e, 1),
+    };
+    if args.flag("json") {
+        eprintln!("{}", serde_json::json!({ "error": e }));
+    } else if code == EXIT_USAGE {
+        eprintln!("{}\n\n{}", e, USAGE);
+    } else {
+        eprintln!("Error: {}", e);
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_with(args: &[&str]) -> i32 {
+        run(args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn parse_separates_options_flags_and_positionals() {
+        let args = Args::parse(
+            ["transcribe", "in.wav", "--model", "ggml-base.bin", "--vad"]
+                .map(String::from)
+                .to_vec(),
+        )
+        .unwrap();
+        assert_eq!(args.positional, ["transcribe", "in.wav"]);
+        assert_eq!(args.option("model"), Some("ggml-base.bin"));
+        assert!(args.flag("vad"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_options() {
+        let error = Args::parse(vec!["probe".into(), "--verbose".into()])
+            .err()
+            .unwrap();
+        assert_eq!(error, "Unknown option --verbose");
+    }
+
+    #[test]
+    fn usage_errors_exit_with_usage_code() {
+        assert_eq!(run_with(&["probe", "in.mp4", "--bogus"]), EXIT_USAGE);
+        assert_eq!(run_with(&["transcribe", "--model"]), EXIT_USAGE);
+        assert_eq!(run_with(&["transcribe", "in.wav"]), EXIT_USAGE);
+        assert_eq!(run_with(&["convert", "--to", "srt"]), EXIT_USAGE);
+        assert_eq!(run_with(&["models", "upgrade"]), EXIT_USAGE);
+        assert_eq!(run_with(&["frobnicate"]), EXIT_USAGE);
+    }
+}
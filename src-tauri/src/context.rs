@@ -0,0 +1,104 @@
+//! Abstraction over the host application for path resolution and event emission, so the
+//! transcription, model and queue logic can run without a Tauri window, e.g. from the CLI
+//! or against temp dirs and fake binaries.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_fs::FsExt;
+
+/// Resolves resource and data directories and delivers events to listeners.
+pub trait AppContext: Clone + Send + Sync + 'static {
+    /// Resolves a path relative to the bundled resources directory.
+    fn resource_path(&self, relative: &str) -> Result<PathBuf, String>;
+
+    /// Returns the per-user app data directory.
+    fn app_data_dir(&self) -> PathBuf;
+
+    /// Returns whether the user granted access to `path`, e.g. by picking it in a file dialog.
+    fn user_selected(&self, path: &Path) -> bool;
+
+    /// Emits an event to listeners. Delivery failures are ignored.
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S);
+}
+
+impl AppContext for AppHandle {
+    fn resource_path(&self, relative: &str) -> Result<PathBuf, String> {
+        self.path()
+            .resolve(relative, BaseDirectory::Resource)
+            .map_err(|e| e.to_string())
+    }
+
+    fn app_data_dir(&self) -> PathBuf {
+        self.path().app_data_dir().unwrap()
+    }
+
+    fn user_selected(&self, path: &Path) -> bool {
+        // The dialog plugin adds picked files to the fs scope
+        self.try_fs_scope()
+            .is_some_and(|scope| scope.is_allowed(path))
+    }
+
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let _ = self.emit(event, payload);
+    }
+}
+
+/// App context backed by plain directories, for the CLI and for running the core logic
+/// outside of Tauri. Events are discarded unless printed to stderr as JSON lines.
+#[derive(Clone, Debug)]
+pub struct PlainContext {
+    pub resource_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub print_events: bool,
+    /// Files the user granted access to, see `AppContext::user_selected`.
+    pub selected_paths: Vec<PathBuf>,
+}
+
+impl PlainContext {
+    /// Creates a context resolving resources under `resource_dir` and storing data in `data_dir`.
+    pub fn new(resource_dir: impl Into<PathBuf>, data_dir: impl Into<PathBuf>) -> Self {
+        PlainContext {
+            resource_dir: resource_dir.into(),
+            data_dir: data_dir.into(),
+            print_events: false,
+            selected_paths: Vec::new(),
+        }
+    }
+}
+
+impl AppContext for PlainContext {
+    fn resource_path(&self, relative: &str) -> Result<PathBuf, String> {
+        Ok(self.resource_dir.join(relative))
+    }
+
+    fn app_data_dir(&self) -> PathBuf {
+        self.data_dir.clone()
+    }
+
+    fn user_selected(&self, path: &Path) -> bool {
+        self.selected_paths.iter().any(|p| p == path)
+    }
+
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if self.print_events {
+            eprintln!(
+                "{}",
+                serde_json::json!({ "event": event, "payload": payload })
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+impl PlainContext {
+    /// Creates a context over fresh `resources` and `data` dirs in the temp dir.
+    pub fn temp() -> Self {
+        let root = std::env::temp_dir().join(format!("captions-test-{}", uuid::Uuid::new_v4()));
+        let context = PlainContext::new(root.join("resources"), root.join("data"));
+        std::fs::create_dir_all(&context.resource_dir).unwrap();
+        std::fs::create_dir_all(&context.data_dir).unwrap();
+        context
+    }
+}
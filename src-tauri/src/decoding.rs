@@ -0,0 +1,122 @@
+//! Persistent whisper decoding presets, so each project can keep its own tuning.
+
+use crate::context::AppContext;
+use crate::storage::{load_json_list, save_json_list};
+use crate::whisper::WhisperOptions;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Name of the built-in preset that keeps whisper-cli's defaults.
+const DEFAULT_PRESET: &str = "Default";
+
+/// Named set of decoding options.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodingPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub options: WhisperOptions,
+}
+
+fn get_presets_path(app: &impl AppContext) -> PathBuf {
+    app.app_data_dir().join("decoding_presets.json")
+}
+
+/// Loads saved presets, always including the built-in `Default` preset unless it was overridden.
+/// Fails if the presets file exists but cannot be read or parsed.
+pub fn load_presets(app: &impl AppContext) -> Result<Vec<DecodingPreset>, String> {
+    let mut presets: Vec<DecodingPreset> =
+        load_json_list(&get_presets_path(app), "decoding presets")?;
+    if !presets.iter().any(|p| p.name == DEFAULT_PRESET) {
+        presets.insert(
+            0,
+            DecodingPreset {
+                name: DEFAULT_PRESET.to_string(),
+                options: WhisperOptions::default(),
+            },
+        );
+    }
+    Ok(presets)
+}
+
+fn save_presets(app: &impl AppContext, presets: &[DecodingPreset]) -> Result<(), String> {
+    save_json_list(&get_presets_path(app), presets, "decoding presets")
+}
+
+/// Looks up a decoding preset by name.
+pub fn get_decoding_preset(app: &impl AppContext, name: &str) -> Result<WhisperOptions, String> {
+    load_presets(app)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .map(|p| p.options)
+        .ok_or_else(|| format!("Decoding preset '{}' not found", name))
+}
+
+/// Tauri command: List all decoding presets, including the built-in `Default`.
+#[tauri::command]
+pub fn list_decoding_presets(app: AppHandle) -> Result<Vec<DecodingPreset>, String> {
+    load_presets(&app)
+}
+
+/// Creates or replaces a decoding preset, keyed by its name. Nothing is saved when the
+/// existing presets could not be loaded.
+fn store_preset(app: &impl AppContext, preset: DecodingPreset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    preset.options.validate()?;
+    let mut presets = load_presets(app)?;
+    match presets.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    save_presets(app, &presets)
+}
+
+/// Tauri command: Create or replace a decoding preset, keyed by its name.
+#[tauri::command]
+pub fn save_decoding_preset(app: AppHandle, preset: DecodingPreset) -> Result<(), String> {
+    store_preset(&app, preset)
+}
+
+/// Tauri command: Delete a decoding preset. Deleting `Default` restores the built-in preset.
+#[tauri::command]
+pub fn delete_decoding_preset(app: AppHandle, name: String) -> Result<(), String> {
+    let mut presets = load_presets(&app)?;
+    let before = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == before {
+        return Err(format!("Decoding preset '{}' not found", name));
+    }
+    save_presets(&app, &presets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+
+    #[test]
+    fn presets_round_trip_through_the_data_dir() {
+        let app = PlainContext::temp();
+        let options = WhisperOptions {
+            beam_size: Some(5),
+            prompt: Some("Tauri, whisper.cpp".to_string()),
+            ..WhisperOptions::default()
+        };
+        store_preset(
+            &app,
+            DecodingPreset {
+                name: "Accurate".to_string(),
+                options: options.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_decoding_preset(&app, "Accurate").unwrap(), options);
+        assert_eq!(
+            get_decoding_preset(&app, DEFAULT_PRESET).unwrap(),
+            WhisperOptions::default()
+        );
+    }
+}
@@ -1,42 +1,961 @@
-//! Utilities for spawning and managing ffmpeg processes for audio conversion.
+//! Utilities for spawning and managing ffmpeg processes for audio conversion and rendering.
 
+use crate::context::AppContext;
+use crate::jobs::{finish_job, register_job, track_process, track_temp_file};
+use crate::probe::{probe, MediaInfo, MediaStream, StreamKind};
+use crate::styles::get_style_preset;
+use crate::subtitles::SubtitleDocument;
+use crate::whisper::document_from_json;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 /// Resolves the path to the ffmpeg binary bundled with the app.
 ///
 /// # Arguments
-/// * `app` - Reference to the Tauri AppHandle.
+/// * `app` - Reference to the app context.
 ///
 /// # Returns
 /// * `Ok(String)` - The resolved path to the ffmpeg binary.
 /// * `Err(String)` - Error message if resolution fails.
-fn resolve_ffmpeg_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
-    app.path()
-        .resolve(
-            "gen/ffmpeg-bin/ffmpeg",
-            tauri::path::BaseDirectory::Resource,
-        )
+pub fn resolve_ffmpeg_path(app: &impl AppContext) -> Result<std::path::PathBuf, String> {
+    app.resource_path("gen/ffmpeg-bin/ffmpeg")
         .map_err(|e| format!("[ffmpeg] Failed to resolve ffmpeg path: {}", e))
 }
 
+/// Optional audio preprocessing applied before transcription.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AudioFilters {
+    /// EBU R128 loudness normalization (`loudnorm`).
+    pub normalize: bool,
+    /// Remove rumble below this frequency in Hz (`highpass`).
+    pub highpass_hz: Option<u32>,
+    /// Remove hiss above this frequency in Hz (`lowpass`).
+    pub lowpass_hz: Option<u32>,
+    /// FFT based noise reduction (`afftdn`).
+    pub denoise: bool,
+    /// Volume change in dB (`volume`).
+    pub gain_db: Option<f32>,
+}
+
+impl AudioFilters {
+    /// Checks that the cutoff frequencies and gain are usable.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.highpass_hz == Some(0) || self.lowpass_hz == Some(0) {
+            return Err("Filter frequencies must be above 0 Hz".to_string());
+        }
+        if let (Some(high), Some(low)) = (self.highpass_hz, self.lowpass_hz) {
+            if high >= low {
+                return Err(
+                    "The high-pass frequency must be below the low-pass frequency".to_string(),
+                );
+            }
+        }
+        if self.gain_db.is_some_and(|g| !g.is_finite()) {
+            return Err("Gain must be a finite number".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the ffmpeg filter chain, or `None` when no filter is enabled.
+    pub fn to_filter_chain(&self) -> Option<String> {
+        let mut filters = Vec::new();
+        if let Some(hz) = self.highpass_hz {
+            filters.push(format!("highpass=f={}", hz));
+        }
+        if let Some(hz) = self.lowpass_hz {
+            filters.push(format!("lowpass=f={}", hz));
+        }
+        if self.denoise {
+            filters.push("afftdn".to_string());
+        }
+        if let Some(gain) = self.gain_db {
+            filters.push(format!("volume={}dB", gain));
+        }
+        // Normalize last so it sees the cleaned-up signal
+        if self.normalize {
+            filters.push("loudnorm".to_string());
+        }
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+}
+
+/// Which part of the input to convert and how to preprocess it.
+#[derive(Debug, Clone, Default)]
+pub struct WavConversion {
+    /// Audio track indices (`0:a:N`) to use. Empty uses ffmpeg's default stream, several are
+    /// mixed down into one.
+    pub audio_tracks: Vec<u32>,
+    /// Start of the range to convert, in milliseconds.
+    pub start_ms: Option<u64>,
+    /// End of the range to convert, in milliseconds.
+    pub end_ms: Option<u64>,
+    pub filters: AudioFilters,
+}
+
+/// Formats milliseconds as seconds for ffmpeg time options.
+fn ffmpeg_seconds(ms: u64) -> String {
+    format!("{}.{:03}", ms / 1000, ms % 1000)
+}
+
+/// Builds the input, range, track and filter arguments of a conversion. `extra_filter` is
+/// appended to the filter chain, e.g. for analysis filters.
+fn conversion_args(
+    input_path: &str,
+    conversion: &WavConversion,
+    extra_filter: Option<&str>,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    let start_ms = conversion.start_ms.unwrap_or(0);
+    // Seeking before -i is fast and, since we decode audio, still sample accurate
+    if start_ms > 0 {
+        args.extend(["-ss".to_string(), ffmpeg_seconds(start_ms)]);
+    }
+    args.extend(["-i".to_string(), input_path.to_string()]);
+    if let Some(end_ms) = conversion.end_ms {
+        args.extend([
+            "-t".to_string(),
+            ffmpeg_seconds(end_ms.saturating_sub(start_ms)),
+        ]);
+    }
+    let filter_chain = match (conversion.filters.to_filter_chain(), extra_filter) {
+        (Some(chain), Some(extra)) => Some(format!("{},{}", chain, extra)),
+        (chain, extra) => chain.or(extra.map(str::to_string)),
+    };
+    if let [_, _, ..] = conversion.audio_tracks.as_slice() {
+        let tracks = &conversion.audio_tracks;
+        let inputs: String = tracks.iter().map(|t| format!("[0:a:{}]", t)).collect();
+        let mut graph = format!("{}amix=inputs={}:duration=longest", inputs, tracks.len());
+        if let Some(chain) = filter_chain {
+            graph.push(',');
+            graph.push_str(&chain);
+        }
+        graph.push_str("[mix]");
+        args.extend([
+            "-filter_complex".to_string(),
+            graph,
+            "-map".to_string(),
+            "[mix]".to_string(),
+        ]);
+    } else {
+        if let Some(track) = conversion.audio_tracks.first() {
+            args.extend(["-map".to_string(), format!("0:a:{}", track)]);
+        }
+        if let Some(chain) = filter_chain {
+            args.extend(["-af".to_string(), chain]);
+        }
+    }
+    args
+}
+
 /// Spawns an ffmpeg process to convert the input file to mono 16kHz WAV format.
 ///
 /// # Arguments
-/// * `app` - Reference to the Tauri AppHandle.
+/// * `app` - Reference to the app context.
 /// * `input_path` - Path to the input audio/video file.
+/// * `conversion` - Audio tracks, time range and filters to apply.
 ///
 /// # Returns
 /// * `Ok(Child)` - The spawned ffmpeg process with stdout piped.
 /// * `Err(String)` - Error message if spawning fails.
-pub fn spawn_ffmpeg_to_wav(app: &AppHandle, input_path: &str) -> Result<Child, String> {
+pub fn spawn_ffmpeg_to_wav(
+    app: &impl AppContext,
+    input_path: &str,
+    conversion: &WavConversion,
+) -> Result<Child, String> {
     let ffmpeg_path = resolve_ffmpeg_path(app)?;
+    let mut args = conversion_args(input_path, conversion, None);
+    args.extend(["-f", "wav", "-ar", "16000", "-ac", "1", "-"].map(String::from));
     Command::new(&ffmpeg_path)
-        .args([
-            "-i", input_path, "-f", "wav", "-ar", "16000", "-ac", "1", "-",
-        ])
+        .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .spawn()
         .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))
 }
+
+/// Converts the input to a mono 16kHz WAV file. Unlike piped output, the file gets a
+/// complete header, so it can be read back with `hound`. The ffmpeg process is registered
+/// with the job so it can be cancelled.
+///
+/// # Arguments
+/// * `app` - Reference to the app context.
+/// * `job_id` - Job the conversion belongs to.
+/// * `input_path` - Path to the input audio/video file.
+/// * `conversion` - Audio tracks, time range and filters to apply.
+/// * `output` - Path of the WAV file to write.
+///
+/// # Returns
+/// * `Ok(())` - The WAV file was written.
+/// * `Err(String)` - Error message if ffmpeg fails.
+pub fn convert_to_wav_file(
+    app: &impl AppContext,
+    job_id: &str,
+    input_path: &str,
+    conversion: &WavConversion,
+    output: &Path,
+) -> Result<(), String> {
+    let ffmpeg_path = resolve_ffmpeg_path(app)?;
+    let mut args = ["-hide_banner", "-v", "error", "-y"]
+        .map(String::from)
+        .to_vec();
+    args.extend(conversion_args(input_path, conversion, None));
+    args.extend(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"].map(String::from));
+    args.push(output.to_string_lossy().into_owned());
+    let mut ffmpeg = Command::new(&ffmpeg_path)
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    let stderr = ffmpeg.stderr.take();
+    let ffmpeg = track_process(job_id, ffmpeg);
+    let mut log = String::new();
+    if let Some(mut stderr) = stderr {
+        let _ = stderr.read_to_string(&mut log);
+    }
+    let status = ffmpeg
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|e| format!("[ffmpeg] Failed to wait for ffmpeg: {}", e))?;
+    if !status.success() {
+        return Err(format!("[ffmpeg] Failed to decode audio: {}", log.trim()));
+    }
+    Ok(())
+}
+
+/// Matches `silencedetect` log lines, e.g. `silence_start: 12.34` or `silence_end: 13.5 | ...`.
+static SILENCE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"silence_(start|end): (-?\d+(?:\.\d+)?)").unwrap());
+
+/// Finds silent stretches in the converted audio with ffmpeg's `silencedetect` filter.
+/// The ffmpeg process is registered with the job so it can be cancelled.
+///
+/// # Arguments
+/// * `app` - Reference to the app context.
+/// * `job_id` - Job the analysis belongs to.
+/// * `input_path` - Path to the input audio/video file.
+/// * `conversion` - Audio tracks, time range and filters, as used for transcription.
+///
+/// # Returns
+/// * `Ok(Vec<(u64, u64)>)` - Start and end of each silence in milliseconds, relative to the
+///   start of the converted range.
+/// * `Err(String)` - Error message if ffmpeg fails.
+pub fn detect_silences(
+    app: &impl AppContext,
+    job_id: &str,
+    input_path: &str,
+    conversion: &WavConversion,
+) -> Result<Vec<(u64, u64)>, String> {
+    let ffmpeg_path = resolve_ffmpeg_path(app)?;
+    let mut args = vec!["-hide_banner".to_string(), "-nostats".to_string()];
+    args.extend(conversion_args(
+        input_path,
+        conversion,
+        Some("silencedetect=noise=-35dB:d=0.5"),
+    ));
+    args.extend(["-f", "null", "-"].map(String::from));
+    let mut ffmpeg = Command::new(&ffmpeg_path)
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    let stderr = ffmpeg.stderr.take();
+    let ffmpeg = track_process(job_id, ffmpeg);
+    let mut log = String::new();
+    if let Some(mut stderr) = stderr {
+        let _ = stderr.read_to_string(&mut log);
+    }
+    let status = ffmpeg
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|e| format!("[ffmpeg] Failed to wait for ffmpeg: {}", e))?;
+    if !status.success() {
+        return Err(format!("[ffmpeg] Silence detection failed: {}", log.trim()));
+    }
+    let mut silences = Vec::new();
+    let mut start = None;
+    for caps in SILENCE_REGEX.captures_iter(&log) {
+        let ms = (caps[2].parse::<f64>().unwrap_or(0.0).max(0.0) * 1000.0).round() as u64;
+        match &caps[1] {
+            "start" => start = Some(ms),
+            _ => {
+                if let Some(start) = start.take() {
+                    silences.push((start, ms));
+                }
+            }
+        }
+    }
+    Ok(silences)
+}
+
+/// Video codec used when rendering.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    H265,
+}
+
+/// Quality/size trade-off used when rendering.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderQuality {
+    High,
+    #[default]
+    Balanced,
+    Small,
+}
+
+/// Options for burning subtitles into a video.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BurnOptions {
+    pub codec: VideoCodec,
+    pub quality: RenderQuality,
+    /// Style preset applied to SRT and JSON input; ASS input keeps its own styles.
+    pub style: Option<String>,
+    /// Highlight words karaoke-style. Needs word timings, so only applies to JSON input: a
+    /// subtitle document or transcription made with word timestamps.
+    pub karaoke: bool,
+}
+
+/// Payload of the `render-progress` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RenderProgress {
+    job_id: String,
+    percent: u32,
+}
+
+/// Lists the encoders the bundled ffmpeg was built with, as printed by `ffmpeg -encoders`.
+fn available_encoders(app: &impl AppContext) -> Result<String, String> {
+    let output = Command::new(resolve_ffmpeg_path(app)?)
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the ffmpeg encoder arguments for a codec and quality preset. Uses x264/x265 when
+/// ffmpeg was built with them (a GPL build, see `scripts/build-ffmpeg.sh`) and VideoToolbox
+/// otherwise.
+fn encoder_args(
+    codec: VideoCodec,
+    quality: RenderQuality,
+    encoders: &str,
+) -> Result<Vec<String>, String> {
+    let has_encoder = |name: &str| {
+        encoders
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(name))
+    };
+    let (software, hardware) = match codec {
+        VideoCodec::H264 => ("libx264", "h264_videotoolbox"),
+        VideoCodec::H265 => ("libx265", "hevc_videotoolbox"),
+    };
+    let mut args: Vec<String> = if has_encoder(software) {
+        let crf = match (codec, quality) {
+            (VideoCodec::H264, RenderQuality::High) => 18,
+            (VideoCodec::H264, RenderQuality::Balanced) => 23,
+            (VideoCodec::H264, RenderQuality::Small) => 28,
+            (VideoCodec::H265, RenderQuality::High) => 22,
+            (VideoCodec::H265, RenderQuality::Balanced) => 28,
+            (VideoCodec::H265, RenderQuality::Small) => 32,
+        };
+        let preset = match quality {
+            RenderQuality::High => "slow",
+            RenderQuality::Balanced => "medium",
+            RenderQuality::Small => "fast",
+        };
+        vec![
+            "-c:v".into(),
+            software.into(),
+            "-crf".into(),
+            crf.to_string(),
+            "-preset".into(),
+            preset.into(),
+        ]
+    } else if has_encoder(hardware) {
+        // VideoToolbox takes a constant quality from 1 to 100 instead of a CRF
+        let quality = match quality {
+            RenderQuality::High => 75,
+            RenderQuality::Balanced => 60,
+            RenderQuality::Small => 45,
+        };
+        vec![
+            "-c:v".into(),
+            hardware.into(),
+            "-q:v".into(),
+            quality.to_string(),
+        ]
+    } else {
+        return Err(format!(
+            "[ffmpeg] The bundled ffmpeg has neither {} nor {} to encode the video",
+            software, hardware
+        ));
+    };
+    args.extend(["-pix_fmt".into(), "yuv420p".into()]);
+    if let VideoCodec::H265 = codec {
+        // Lets QuickTime and Apple devices play the file
+        args.extend(["-tag:v".into(), "hvc1".into()]);
+    }
+    Ok(args)
+}
+
+/// Resolves a possibly relative path against the current directory, since the render runs
+/// with the temp dir as its working directory.
+fn absolute_path(path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .map_err(|e| format!("[ffmpeg] Failed to resolve {}: {}", path.display(), e))
+}
+
+/// Returns a hidden path next to `output` with the same extension, so ffmpeg still picks the
+/// format from it. Renders are written there and only renamed over `output` once they succeed,
+/// so a failed or cancelled render never touches a file that was already there.
+fn staging_path(output: &Path) -> PathBuf {
+    let name = format!(".captions-{}", uuid::Uuid::new_v4());
+    output.with_file_name(match output.extension() {
+        Some(extension) => format!("{}.{}", name, extension.to_string_lossy()),
+        None => name,
+    })
+}
+
+/// Moves a finished render from its staging path to `output`.
+fn publish_output(staging: &Path, output: &Path) -> Result<(), String> {
+    std::fs::rename(staging, output).map_err(|e| {
+        let _ = std::fs::remove_file(staging);
+        format!("[ffmpeg] Failed to write {}: {}", output.display(), e)
+    })
+}
+
+/// Writes the subtitles as an ASS file in the temp dir, converting SRT with the chosen style.
+/// Returns the file name; ffmpeg runs inside the temp dir so the filter argument needs no
+/// escaping.
+fn prepare_ass_file(
+    app: &impl AppContext,
+    job_id: &str,
+    subtitle_path: &str,
+    options: &BurnOptions,
+) -> Result<String, String> {
+    let contents = std::fs::read_to_string(subtitle_path)
+        .map_err(|e| format!("[ffmpeg] Failed to read {}: {}", subtitle_path, e))?;
+    let extension = Path::new(subtitle_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let ass = if extension == "ass" || extension == "ssa" {
+        contents
+    } else {
+        // SRT carries no word timings, JSON documents and transcriptions can
+        let document = if extension == "json" {
+            document_from_json(&contents)
+                .map_err(|e| format!("[ffmpeg] Failed to parse {}: {}", subtitle_path, e))?
+        } else {
+            SubtitleDocument::from_srt(&contents)
+        };
+        if document.cues.is_empty() {
+            return Err(format!(
+                "[ffmpeg] No subtitle cues found in {}",
+                subtitle_path
+            ));
+        }
+        if options.karaoke && document.cues.iter().all(|c| c.words.is_empty()) {
+            return Err(format!(
+                "[ffmpeg] Karaoke needs word timings, which {} does not have",
+                subtitle_path
+            ));
+        }
+        let style = get_style_preset(app, options.style.as_deref().unwrap_or("Default"))?;
+        document.to_ass(&style, options.karaoke)?
+    };
+    let file_name = format!("captions-{}.ass", uuid::Uuid::new_v4());
+    let path = std::env::temp_dir().join(&file_name);
+    std::fs::write(&path, ass)
+        .map_err(|e| format!("[ffmpeg] Failed to write subtitle file: {}", e))?;
+    track_temp_file(job_id, path);
+    Ok(file_name)
+}
+
+/// Runs the ffmpeg render for a registered job, emitting `render-progress` events parsed from
+/// ffmpeg's `-progress` output.
+fn run_burn(
+    app: &impl AppContext,
+    job_id: &str,
+    video_path: &str,
+    subtitle_path: &str,
+    output_path: &str,
+    options: &BurnOptions,
+) -> Result<(), String> {
+    let output = absolute_path(output_path)?;
+    if output == absolute_path(video_path)? {
+        return Err("[ffmpeg] The output file must differ from the source video".to_string());
+    }
+    let info = probe(app, video_path)?;
+    if !info.streams.iter().any(|s| s.kind == StreamKind::Video) {
+        return Err(format!("[ffmpeg] {} has no video stream", video_path));
+    }
+    let ass_file = prepare_ass_file(app, job_id, subtitle_path, options)?;
+    let ffmpeg_path = resolve_ffmpeg_path(app)?;
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-v".to_string(),
+        "error".to_string(),
+        "-nostats".to_string(),
+        "-progress".to_string(),
+        "pipe:1".to_string(),
+        "-y".to_string(),
+        "-i".to_string(),
+        absolute_path(video_path)?.to_string_lossy().into_owned(),
+        "-vf".to_string(),
+        format!("ass={}", ass_file),
+    ];
+    args.extend(encoder_args(
+        options.codec,
+        options.quality,
+        &available_encoders(app)?,
+    )?);
+    args.extend(
+        ["-c:a", "copy", "-movflags", "+faststart"]
+            .iter()
+            .map(|a| a.to_string()),
+    );
+    let staging = staging_path(&output);
+    track_temp_file(job_id, staging.clone());
+    args.push(staging.to_string_lossy().into_owned());
+
+    let mut ffmpeg = Command::new(&ffmpeg_path)
+        .args(&args)
+        .current_dir(std::env::temp_dir())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    let stdout = ffmpeg.stdout.take();
+    let stderr = ffmpeg.stderr.take();
+    let ffmpeg = track_process(job_id, ffmpeg);
+    // Drain stderr on its own thread so the pipe cannot fill up and block ffmpeg
+    let stderr_thread = std::thread::spawn(move || {
+        let mut log = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut log);
+        }
+        log
+    });
+    let duration_ms = info.duration_ms.unwrap_or(0);
+    let mut last_percent = None;
+    if let Some(stdout) = stdout {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Some(out_time_us) = line.strip_prefix("out_time_us=") else {
+                continue;
+            };
+            let Ok(out_time_us) = out_time_us.trim().parse::<u64>() else {
+                continue;
+            };
+            if duration_ms == 0 {
+                continue;
+            }
+            let percent = (out_time_us / 1000 * 100 / duration_ms).min(99) as u32;
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                app.emit_event(
+                    "render-progress",
+                    RenderProgress {
+                        job_id: job_id.to_string(),
+                        percent,
+                    },
+                );
+            }
+        }
+    }
+    let status = ffmpeg
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|e| format!("[ffmpeg] Failed to wait for ffmpeg: {}", e))?;
+    let log = stderr_thread.join().unwrap_or_default();
+    if !status.success() {
+        // Killed or failed renders leave a truncated file behind
+        let _ = std::fs::remove_file(&staging);
+        if log.contains("No such filter: 'ass'") {
+            return Err(
+                "[ffmpeg] The bundled ffmpeg was built without libass, install libass and rebuild it to burn in subtitles"
+                    .to_string(),
+            );
+        }
+        return Err(format!("[ffmpeg] Rendering failed: {}", log.trim()));
+    }
+    publish_output(&staging, &output)?;
+    app.emit_event(
+        "render-progress",
+        RenderProgress {
+            job_id: job_id.to_string(),
+            percent: 100,
+        },
+    );
+    Ok(())
+}
+
+/// Renders a copy of a video with subtitles burned in. Runs as a job, so it reports
+/// `transcription-status` events and can be stopped with `cancel_transcription`. The render
+/// only replaces `output_path` once it succeeded.
+pub fn burn_subtitles_job(
+    app: &impl AppContext,
+    job_id: &str,
+    video_path: &str,
+    subtitle_path: &str,
+    output_path: &str,
+    options: &BurnOptions,
+) -> Result<(), String> {
+    register_job(app, job_id)?;
+    let result = run_burn(app, job_id, video_path, subtitle_path, output_path, options);
+    finish_job(app, job_id, result)
+}
+
+/// Tauri command: Burn SRT, ASS or JSON subtitles into a video.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `video_path` - Path to the source video
+/// * `subtitle_path` - Path to an `.srt`, `.ass` or `.json` (subtitle document or
+///   transcription) file
+/// * `output_path` - Path of the rendered video
+/// * `options` - Optional codec, quality and style settings
+/// * `job_id` - Id attached to progress events and used by `cancel_transcription`,
+///   a new UUID is used when omitted
+///
+/// # Returns
+/// * `Ok(())` - The video was rendered to `output_path`
+/// * `Err(String)` - Error message, or `cancelled` if the render was cancelled
+#[tauri::command]
+pub async fn burn_subtitles(
+    app: AppHandle,
+    video_path: String,
+    subtitle_path: String,
+    output_path: String,
+    options: Option<BurnOptions>,
+    job_id: Option<String>,
+) -> Result<(), String> {
+    let job_id = job_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    burn_subtitles_job(
+        &app,
+        &job_id,
+        &video_path,
+        &subtitle_path,
+        &output_path,
+        &options.unwrap_or_default(),
+    )
+}
+
+/// Text format of a muxed subtitle track. MP4 output always stores `mov_text`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleTrackFormat {
+    #[default]
+    Srt,
+    Ass,
+}
+
+/// A subtitle track to embed into a video container.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleTrack {
+    pub document: SubtitleDocument,
+    /// ISO 639-2 language code, e.g. `eng`.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub format: SubtitleTrackFormat,
+    /// Style preset for ASS tracks.
+    #[serde(default)]
+    pub style: Option<String>,
+    #[serde(default)]
+    pub default: bool,
+    #[serde(default)]
+    pub forced: bool,
+}
+
+/// Removes the temp files on drop, so every early return cleans up.
+struct TempFiles(Vec<PathBuf>);
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Returns the `-map` specifiers of the subtitle streams kept from the source video. MP4
+/// only holds text subtitles (`mov_text`), so bitmap tracks like PGS or DVD subtitles are
+/// left out there; MKV keeps them all.
+fn kept_subtitle_maps(info: &MediaInfo, is_mp4: bool) -> Vec<String> {
+    let streams: Vec<&MediaStream> = if is_mp4 {
+        info.text_subtitle_streams().collect()
+    } else {
+        info.streams
+            .iter()
+            .filter(|s| s.kind == StreamKind::Subtitle)
+            .collect()
+    };
+    streams.iter().map(|s| format!("0:{}", s.index)).collect()
+}
+
+/// Copies the video's streams into a new MP4 or MKV file and adds the given subtitle tracks.
+/// Bitmap subtitle tracks of the source are dropped when writing MP4.
+///
+/// # Arguments
+/// * `app` - Reference to the app context.
+/// * `video_path` - Path to the source video.
+/// * `output_path` - Path of the new file; its extension selects the container.
+/// * `tracks` - Subtitle tracks to add after any the video already has.
+///
+/// # Returns
+/// * `Ok(())` - The file was written to `output_path`.
+/// * `Err(String)` - Error message if conversion or ffmpeg fails.
+pub fn mux_subtitle_tracks(
+    app: &impl AppContext,
+    video_path: &str,
+    output_path: &str,
+    tracks: &[SubtitleTrack],
+) -> Result<(), String> {
+    if tracks.is_empty() {
+        return Err("[ffmpeg] No subtitle tracks to add".to_string());
+    }
+    let output = absolute_path(output_path)?;
+    if output == absolute_path(video_path)? {
+        return Err("[ffmpeg] The output file must differ from the source video".to_string());
+    }
+    let extension = Path::new(output_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let is_mp4 = match extension.as_str() {
+        "mp4" | "m4v" | "mov" => true,
+        "mkv" => false,
+        other => {
+            return Err(format!(
+                "[ffmpeg] Unsupported container for subtitle tracks: .{}",
+                other
+            ))
+        }
+    };
+    let kept = kept_subtitle_maps(&probe(app, video_path)?, is_mp4);
+    let existing = kept.len();
+
+    let mut temp_files = TempFiles(Vec::new());
+    let mut inputs = Vec::new();
+    for track in tracks {
+        let (contents, extension) = if track.format == SubtitleTrackFormat::Ass && !is_mp4 {
+            let style = get_style_preset(app, track.style.as_deref().unwrap_or("Default"))?;
+            (track.document.to_ass(&style, false)?, "ass")
+        } else {
+            (track.document.to_srt(), "srt")
+        };
+        let path =
+            std::env::temp_dir().join(format!("captions-{}.{}", uuid::Uuid::new_v4(), extension));
+        std::fs::write(&path, contents)
+            .map_err(|e| format!("[ffmpeg] Failed to write subtitle file: {}", e))?;
+        temp_files.0.push(path.clone());
+        inputs.push(path);
+    }
+
+    let mut args = vec!["-hide_banner", "-v", "error", "-y", "-i", video_path]
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    for input in &inputs {
+        args.extend(["-i".to_string(), input.to_string_lossy().into_owned()]);
+    }
+    args.extend(["-map", "0:v?", "-map", "0:a?"].map(String::from));
+    for map in kept {
+        args.extend(["-map".to_string(), map]);
+    }
+    for i in 1..=inputs.len() {
+        args.extend(["-map".to_string(), i.to_string()]);
+    }
+    args.extend(["-c", "copy"].map(String::from));
+    if is_mp4 {
+        args.extend(["-c:s", "mov_text"].map(String::from));
+    }
+    // A new default track replaces the default flag of the existing ones
+    if tracks.iter().any(|t| t.default) {
+        for i in 0..existing {
+            args.extend([format!("-disposition:s:{}", i), "0".to_string()]);
+        }
+    }
+    for (i, track) in tracks.iter().enumerate() {
+        let index = existing + i;
+        if let Some(language) = &track.language {
+            args.extend([
+                format!("-metadata:s:s:{}", index),
+                format!("language={}", language),
+            ]);
+        }
+        if let Some(title) = &track.title {
+            args.extend([
+                format!("-metadata:s:s:{}", index),
+                format!("title={}", title),
+            ]);
+        }
+        let disposition = match (track.default, track.forced) {
+            (true, true) => "default+forced",
+            (true, false) => "default",
+            (false, true) => "forced",
+            (false, false) => "0",
+        };
+        args.extend([format!("-disposition:s:{}", index), disposition.to_string()]);
+    }
+    let staging = staging_path(&output);
+    temp_files.0.push(staging.clone());
+    args.push(staging.to_string_lossy().into_owned());
+
+    let result = Command::new(resolve_ffmpeg_path(app)?)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    if !result.status.success() {
+        return Err(format!(
+            "[ffmpeg] Adding subtitle tracks failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+    publish_output(&staging, &output)
+}
+
+/// Tauri command: Embed subtitle documents as selectable tracks in an MP4 or MKV copy of
+/// a video, without re-encoding its video and audio.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `video_path` - Path to the source video
+/// * `output_path` - Path of the new `.mp4`, `.m4v`, `.mov` or `.mkv` file
+/// * `tracks` - Subtitle tracks with language, title and default/forced flags
+///
+/// # Returns
+/// * `Ok(())` - The file was written to `output_path`
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub async fn mux_subtitles(
+    app: AppHandle,
+    video_path: String,
+    output_path: String,
+    tracks: Vec<SubtitleTrack>,
+) -> Result<(), String> {
+    mux_subtitle_tracks(&app, &video_path, &output_path, &tracks)
+}
+
+/// Extracts an embedded text subtitle stream and parses it into a subtitle document.
+///
+/// # Arguments
+/// * `app` - Reference to the app context.
+/// * `input_path` - Path to the media file.
+/// * `stream` - Index among the file's subtitle streams (`0:s:N`).
+///
+/// # Returns
+/// * `Ok(SubtitleDocument)` - The extracted cues.
+/// * `Err(String)` - The stream does not exist, is image-based or ffmpeg fails.
+pub fn extract_subtitle_stream(
+    app: &impl AppContext,
+    input_path: &str,
+    stream: u32,
+) -> Result<SubtitleDocument, String> {
+    let info = probe(app, input_path)?;
+    let subtitle = info
+        .streams
+        .iter()
+        .find(|s| s.kind == StreamKind::Subtitle && s.type_index == stream)
+        .ok_or_else(|| format!("[ffmpeg] Subtitle stream {} does not exist", stream))?;
+    if !info
+        .text_subtitle_streams()
+        .any(|s| s.index == subtitle.index)
+    {
+        return Err(format!(
+            "[ffmpeg] Subtitle stream {} is image-based ({}) and cannot be converted to text",
+            stream, subtitle.codec
+        ));
+    }
+    let output = Command::new(resolve_ffmpeg_path(app)?)
+        .args(["-hide_banner", "-v", "error", "-i", input_path, "-map"])
+        .arg(format!("0:s:{}", stream))
+        .args(["-f", "srt", "-"])
+        .output()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "[ffmpeg] Failed to extract subtitles: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    SubtitleDocument::from_srt(&String::from_utf8_lossy(&output.stdout))
+        .require_cues("the subtitle stream")
+}
+
+/// Tauri command: Extract an embedded text subtitle stream as a subtitle document, so it can
+/// be edited and exported like generated captions.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `input_path` - Path to the media file
+/// * `stream` - Index among the file's subtitle streams, as returned by `list_subtitle_streams`
+///
+/// # Returns
+/// * `Ok(SubtitleDocument)` - The extracted cues
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub async fn extract_subtitles(
+    app: AppHandle,
+    input_path: String,
+    stream: u32,
+) -> Result<SubtitleDocument, String> {
+    extract_subtitle_stream(&app, &input_path, stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probe::parse_ffmpeg_probe;
+
+    const PROBE_OUTPUT: &str = "\
+Input #0, matroska,webm, from 'movie.mkv':
+  Duration: 01:30:00.00, start: 0.000000, bitrate: 8000 kb/s
+  Stream #0:0: Video: h264 (High), yuv420p, 1920x1080, 23.98 fps (default)
+  Stream #0:1(eng): Audio: ac3, 48000 Hz, 5.1(side), fltp, 640 kb/s (default)
+  Stream #0:2(eng): Subtitle: hdmv_pgs_subtitle (default)
+  Stream #0:3(eng): Subtitle: subrip
+  Stream #0:4(ger): Subtitle: dvd_subtitle, 720x480
+  Stream #0:5(ger): Subtitle: ass";
+
+    #[test]
+    fn mp4_keeps_only_text_subtitles() {
+        let info = parse_ffmpeg_probe(PROBE_OUTPUT);
+        assert_eq!(kept_subtitle_maps(&info, true), ["0:3", "0:5"]);
+    }
+
+    #[test]
+    fn mkv_keeps_all_subtitles() {
+        let info = parse_ffmpeg_probe(PROBE_OUTPUT);
+        assert_eq!(
+            kept_subtitle_maps(&info, false),
+            ["0:2", "0:3", "0:4", "0:5"]
+        );
+    }
+}
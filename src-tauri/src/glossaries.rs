@@ -0,0 +1,285 @@
+//! Persistent glossaries of domain terms. Their terms prime whisper through `--prompt` and
+//! their patterns correct misspellings in the transcribed text.
+
+use crate::context::AppContext;
+use crate::storage::{load_json_list, save_json_list};
+use crate::subtitles::{ReplacementRule, Replacements, SubtitleDocument};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// A term with its correct spelling and, optionally, a misspelling to replace with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryEntry {
+    /// Correct spelling, also listed in the prompt.
+    pub term: String,
+    /// Text to replace with `term`. Entries without one only go into the prompt.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Treat `pattern` as a regular expression.
+    #[serde(default)]
+    pub regex: bool,
+}
+
+/// A named list of terms.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Glossary {
+    pub name: String,
+    pub entries: Vec<GlossaryEntry>,
+}
+
+/// Header row of exported CSV files.
+const CSV_HEADER: [&str; 3] = ["term", "pattern", "regex"];
+
+impl Glossary {
+    /// Checks the name and terms and that every pattern compiles.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Glossary name cannot be empty".to_string());
+        }
+        if self.entries.iter().any(|e| e.term.trim().is_empty()) {
+            return Err("Glossary terms cannot be empty".to_string());
+        }
+        self.replacements().map(|_| ())
+    }
+
+    /// Returns the distinct terms as a comma-separated prompt, or `None` for an empty
+    /// glossary. whisper only keeps the last 224 prompt tokens, so very long glossaries lose
+    /// their first terms.
+    pub fn prompt(&self) -> Option<String> {
+        let mut terms: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            let term = entry.term.trim();
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+        (!terms.is_empty()).then(|| terms.join(", "))
+    }
+
+    /// Compiles the entries with a pattern into replacement rules.
+    pub fn replacements(&self) -> Result<Replacements, String> {
+        let rules: Vec<ReplacementRule> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                Some(ReplacementRule {
+                    pattern: entry.pattern.clone()?,
+                    replacement: entry.term.trim().to_string(),
+                    regex: entry.regex,
+                })
+            })
+            .collect();
+        Replacements::new(&rules)
+    }
+
+    /// Parses a CSV file with `term,pattern,regex` columns. The header row is optional and
+    /// only `term` is required; `regex` accepts `true`, `yes` or `1`.
+    pub fn from_csv(name: &str, contents: &str) -> Result<Glossary, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+        let mut entries = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("Failed to parse glossary CSV: {}", e))?;
+            let field = |index: usize| record.get(index).filter(|f| !f.is_empty());
+            if i == 0 && field(0).is_some_and(|f| f.eq_ignore_ascii_case(CSV_HEADER[0])) {
+                continue;
+            }
+            let Some(term) = field(0) else {
+                continue;
+            };
+            entries.push(GlossaryEntry {
+                term: term.to_string(),
+                pattern: field(1).map(str::to_string),
+                regex: field(2).is_some_and(|f| {
+                    ["true", "yes", "1"]
+                        .iter()
+                        .any(|v| f.eq_ignore_ascii_case(v))
+                }),
+            });
+        }
+        let glossary = Glossary {
+            name: name.to_string(),
+            entries,
+        };
+        glossary.validate()?;
+        Ok(glossary)
+    }
+
+    /// Serializes the entries as CSV with a header row.
+    pub fn to_csv(&self) -> Result<String, String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let write_error = |e: csv::Error| format!("Failed to write glossary CSV: {}", e);
+        writer.write_record(CSV_HEADER).map_err(write_error)?;
+        for entry in &self.entries {
+            writer
+                .write_record([
+                    entry.term.as_str(),
+                    entry.pattern.as_deref().unwrap_or(""),
+                    if entry.regex { "true" } else { "false" },
+                ])
+                .map_err(write_error)?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| format!("Failed to write glossary CSV: {}", e))?;
+        String::from_utf8(bytes).map_err(|e| format!("Failed to write glossary CSV: {}", e))
+    }
+}
+
+fn get_glossaries_path(app: &impl AppContext) -> PathBuf {
+    app.app_data_dir().join("glossaries.json")
+}
+
+/// Loads the saved glossaries.
+///
+/// # Returns
+/// * `Ok(Vec<Glossary>)` - The glossaries, empty if none were saved yet
+/// * `Err(String)` - The glossaries file exists but could not be read or parsed
+pub fn load_glossaries(app: &impl AppContext) -> Result<Vec<Glossary>, String> {
+    load_json_list(&get_glossaries_path(app), "glossaries")
+}
+
+fn save_glossaries(app: &impl AppContext, glossaries: &[Glossary]) -> Result<(), String> {
+    save_json_list(&get_glossaries_path(app), glossaries, "glossaries")
+}
+
+/// Creates or replaces a glossary, keyed by its name. Nothing is saved when the existing
+/// glossaries could not be loaded.
+fn store_glossary(app: &impl AppContext, glossary: Glossary) -> Result<(), String> {
+    glossary.validate()?;
+    let mut glossaries = load_glossaries(app)?;
+    match glossaries.iter_mut().find(|g| g.name == glossary.name) {
+        Some(existing) => *existing = glossary,
+        None => glossaries.push(glossary),
+    }
+    save_glossaries(app, &glossaries)
+}
+
+/// Looks up a glossary by name.
+pub fn get_glossary(app: &impl AppContext, name: &str) -> Result<Glossary, String> {
+    load_glossaries(app)?
+        .into_iter()
+        .find(|g| g.name == name)
+        .ok_or_else(|| format!("Glossary '{}' not found", name))
+}
+
+/// Tauri command: List all saved glossaries.
+#[tauri::command]
+pub fn list_glossaries(app: AppHandle) -> Result<Vec<Glossary>, String> {
+    load_glossaries(&app)
+}
+
+/// Tauri command: Create or replace a glossary, keyed by its name.
+#[tauri::command]
+pub fn save_glossary(app: AppHandle, glossary: Glossary) -> Result<(), String> {
+    store_glossary(&app, glossary)
+}
+
+/// Tauri command: Delete a glossary.
+#[tauri::command]
+pub fn delete_glossary(app: AppHandle, name: String) -> Result<(), String> {
+    let mut glossaries = load_glossaries(&app)?;
+    let before = glossaries.len();
+    glossaries.retain(|g| g.name != name);
+    if glossaries.len() == before {
+        return Err(format!("Glossary '{}' not found", name));
+    }
+    save_glossaries(&app, &glossaries)
+}
+
+/// Tauri command: Import CSV content as a glossary, replacing any glossary with that name.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `name` - Name to save the glossary under
+/// * `csv` - CSV with `term,pattern,regex` columns
+///
+/// # Returns
+/// * `Ok(Glossary)` - The imported glossary
+/// * `Err(String)` - Error message if the CSV or a pattern is invalid
+#[tauri::command]
+pub fn import_glossary_csv(app: AppHandle, name: String, csv: String) -> Result<Glossary, String> {
+    let glossary = Glossary::from_csv(&name, &csv)?;
+    store_glossary(&app, glossary.clone())?;
+    Ok(glossary)
+}
+
+/// Tauri command: Export a glossary as CSV.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `name` - Name of the glossary
+///
+/// # Returns
+/// * `Ok(String)` - CSV with a `term,pattern,regex` header row
+/// * `Err(String)` - Error message if the glossary does not exist
+#[tauri::command]
+pub fn export_glossary_csv(app: AppHandle, name: String) -> Result<String, String> {
+    get_glossary(&app, &name)?.to_csv()
+}
+
+/// Tauri command: Apply a glossary's corrections to a subtitle document.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `document` - The subtitle document
+/// * `name` - Name of the glossary
+///
+/// # Returns
+/// * `Ok(SubtitleDocument)` - The document with corrected cue and word text
+/// * `Err(String)` - Error message if the glossary does not exist
+#[tauri::command]
+pub fn apply_glossary(
+    app: AppHandle,
+    mut document: SubtitleDocument,
+    name: String,
+) -> Result<SubtitleDocument, String> {
+    let replacements = get_glossary(&app, &name)?.replacements()?;
+    document.apply_replacements(&replacements);
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+
+    fn glossary() -> Glossary {
+        Glossary {
+            name: "Product".to_string(),
+            entries: vec![
+                GlossaryEntry {
+                    term: "Tauri".to_string(),
+                    pattern: Some("tory".to_string()),
+                    regex: false,
+                },
+                GlossaryEntry {
+                    term: "whisper.cpp".to_string(),
+                    pattern: None,
+                    regex: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn glossaries_round_trip_through_the_data_dir() {
+        let app = PlainContext::temp();
+        assert!(load_glossaries(&app).unwrap().is_empty());
+        store_glossary(&app, glossary()).unwrap();
+        assert_eq!(get_glossary(&app, "Product").unwrap(), glossary());
+        assert!(get_glossary(&app, "Other").is_err());
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_entries() {
+        let csv = glossary().to_csv().unwrap();
+        assert_eq!(Glossary::from_csv("Product", &csv).unwrap(), glossary());
+    }
+}
@@ -0,0 +1,138 @@
+//! Registry of running transcription and render jobs, so their child processes can be
+//! cancelled.
+
+use crate::context::AppContext;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+
+/// Error returned by a job that was cancelled, distinct from any failure message.
+pub const CANCELLED_ERROR: &str = "cancelled";
+
+/// Lifecycle status of a job.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of the `transcription-status` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JobStatusEvent {
+    job_id: String,
+    status: JobStatus,
+    error: Option<String>,
+}
+
+/// Processes and temp files owned by a running job.
+#[derive(Default)]
+struct JobHandle {
+    processes: Vec<Arc<Mutex<Child>>>,
+    temp_files: Vec<PathBuf>,
+    cancelled: bool,
+}
+
+static JOBS: Lazy<Arc<Mutex<HashMap<String, JobHandle>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+fn emit_job_status(app: &impl AppContext, job_id: &str, status: JobStatus, error: Option<String>) {
+    app.emit_event(
+        "transcription-status",
+        JobStatusEvent {
+            job_id: job_id.to_string(),
+            status,
+            error,
+        },
+    );
+}
+
+fn remove_temp_files(handle: &JobHandle) {
+    for path in &handle.temp_files {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Registers a new running job. Fails if a job with the same id is still running.
+pub fn register_job(app: &impl AppContext, job_id: &str) -> Result<(), String> {
+    let mut jobs = JOBS.lock().unwrap();
+    if jobs.contains_key(job_id) {
+        return Err(format!("Job {} is already running", job_id));
+    }
+    jobs.insert(job_id.to_string(), JobHandle::default());
+    emit_job_status(app, job_id, JobStatus::Running, None);
+    Ok(())
+}
+
+/// Hands a child process to the registry so it can be killed on cancel.
+/// If the job was already cancelled, the process is killed immediately.
+pub fn track_process(job_id: &str, child: Child) -> Arc<Mutex<Child>> {
+    let child = Arc::new(Mutex::new(child));
+    let mut jobs = JOBS.lock().unwrap();
+    match jobs.get_mut(job_id) {
+        Some(handle) if handle.cancelled => {
+            let _ = child.lock().unwrap().kill();
+        }
+        Some(handle) => handle.processes.push(child.clone()),
+        None => {}
+    }
+    child
+}
+
+/// Records a temp file to delete when the job finishes or is cancelled.
+pub fn track_temp_file(job_id: &str, path: PathBuf) {
+    if let Some(handle) = JOBS.lock().unwrap().get_mut(job_id) {
+        handle.temp_files.push(path);
+    }
+}
+
+/// Removes a job from the registry, cleans up its temp files and emits its terminal status.
+/// Cancellation takes precedence over the result, since killed processes also report errors.
+pub fn finish_job<T>(
+    app: &impl AppContext,
+    job_id: &str,
+    result: Result<T, String>,
+) -> Result<T, String> {
+    let handle = JOBS.lock().unwrap().remove(job_id);
+    let cancelled = handle.as_ref().map(|h| h.cancelled).unwrap_or(false);
+    if let Some(handle) = &handle {
+        remove_temp_files(handle);
+    }
+    match result {
+        _ if cancelled => {
+            emit_job_status(app, job_id, JobStatus::Cancelled, None);
+            Err(CANCELLED_ERROR.to_string())
+        }
+        Ok(value) => {
+            emit_job_status(app, job_id, JobStatus::Completed, None);
+            Ok(value)
+        }
+        Err(e) => {
+            emit_job_status(app, job_id, JobStatus::Failed, Some(e.clone()));
+            Err(e)
+        }
+    }
+}
+
+/// Tauri command: Cancel a running transcription or render, killing its ffmpeg and whisper
+/// processes and deleting its temp files. The job's `transcribe_file` or `burn_subtitles`
+/// call then returns `cancelled`.
+#[tauri::command]
+pub fn cancel_transcription(job_id: String) -> Result<(), String> {
+    let mut jobs = JOBS.lock().unwrap();
+    let handle = jobs
+        .get_mut(&job_id)
+        .ok_or_else(|| format!("Job {} is not running", job_id))?;
+    handle.cancelled = true;
+    for process in &handle.processes {
+        let _ = process.lock().unwrap().kill();
+    }
+    remove_temp_files(handle);
+    Ok(())
+}
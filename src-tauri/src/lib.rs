@@ -1,15 +1,30 @@
 //! Tauri application setup and command registration.
 
+mod cli;
+mod context;
+mod decoding;
 mod ffmpeg;
+mod glossaries;
+mod jobs;
 mod models;
+mod probe;
+mod queue;
+mod storage;
+mod styles;
 mod subtitles;
+mod vad;
 mod whisper;
 use crate::models::{
     download_model, is_model_resumable, list_models, list_remote_models, pause_model_download,
     remove_model
 };
 use tauri::{TitleBarStyle, WebviewUrl, WebviewWindowBuilder};
-use whisper::{run_whisper_cli, transcribe_file};
+use whisper::{run_whisper_cli, transcribe_file, transcription_to_document};
+
+/// Runs the headless command-line interface with the process arguments and returns its exit code.
+pub fn run_cli() -> i32 {
+    cli::run(std::env::args().skip(1).collect())
+}
 
 /// Initializes and runs the Tauri application, registering all backend commands and plugins.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -48,6 +63,9 @@ pub fn run() {
                 }
             }
 
+            // pick up batch jobs left pending by a previous session
+            queue::resume_queue(app.handle());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -55,12 +73,48 @@ pub fn run() {
             list_models,
             download_model,
             transcribe_file,
+            transcription_to_document,
+            probe::probe_media,
+            ffmpeg::burn_subtitles,
+            ffmpeg::mux_subtitles,
+            probe::list_subtitle_streams,
+            ffmpeg::extract_subtitles,
+            jobs::cancel_transcription,
+            queue::enqueue_transcriptions,
+            queue::get_queue,
+            queue::set_queue_concurrency,
+            queue::retry_queue_item,
+            queue::remove_queue_item,
+            queue::clear_finished_queue_items,
+            queue::get_queue_result,
             list_remote_models,
             remove_model,
             is_model_resumable,
             pause_model_download,
             subtitles::filter_srt_timestamps,
             subtitles::whisper_to_srt,
+            subtitles::whisper_to_vtt,
+            subtitles::srt_to_vtt,
+            subtitles::whisper_to_ass,
+            subtitles::srt_to_ass,
+            subtitles::parse_whisper_output,
+            subtitles::parse_srt,
+            subtitles::document_to_srt,
+            subtitles::document_to_vtt,
+            subtitles::document_to_ass,
+            subtitles::document_to_text,
+            styles::list_style_presets,
+            styles::save_style_preset,
+            styles::delete_style_preset,
+            decoding::list_decoding_presets,
+            decoding::save_decoding_preset,
+            decoding::delete_decoding_preset,
+            glossaries::list_glossaries,
+            glossaries::save_glossary,
+            glossaries::delete_glossary,
+            glossaries::import_glossary_csv,
+            glossaries::export_glossary_csv,
+            glossaries::apply_glossary,
             models::get_model_states,
         ])
         .run(tauri::generate_context!())
@@ -1,5 +1,6 @@
 //! Model management: download, pause, resume, remove, and state tracking for Whisper models.
 
+use crate::context::AppContext;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
@@ -12,7 +13,23 @@ use std::path::PathBuf;
 use std::sync::Once;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::AppHandle;
+
+/// Prints a model management trace to stderr, only when `CAPTIONS_DEBUG` is set so the
+/// CLI's stderr stays clean by default.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if debug_enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+fn debug_enabled() -> bool {
+    static ENABLED: Lazy<bool> =
+        Lazy::new(|| std::env::var_os("CAPTIONS_DEBUG").is_some_and(|v| !v.is_empty()));
+    *ENABLED
+}
 
 /// Represents a remote model available for download.
 #[derive(serde::Serialize)]
@@ -93,7 +110,7 @@ static FINALIZING_LOCKS: Lazy<Arc<Mutex<HashSet<String>>>> =
     Lazy::new(|| Arc::new(Mutex::new(HashSet::new())));
 
 /// Call this at the start of every command to ensure state is loaded and cleaned up.
-fn init_model_states(app: &AppHandle) {
+fn init_model_states(app: &impl AppContext) {
     INIT.call_once(|| {
         {
             let mut states = MODEL_STATES.lock().unwrap();
@@ -110,15 +127,15 @@ fn init_model_states(app: &AppHandle) {
 }
 
 /// Returns the models directory path.
-pub fn get_models_dir(app: &tauri::AppHandle) -> std::path::PathBuf {
-    app.path().app_data_dir().unwrap().join("models")
+pub fn get_models_dir(app: &impl AppContext) -> std::path::PathBuf {
+    app.app_data_dir().join("models")
 }
 
-fn get_states_path(app: &AppHandle) -> PathBuf {
+fn get_states_path(app: &impl AppContext) -> PathBuf {
     get_models_dir(app).join("model_states.json")
 }
 
-fn load_states(app: &AppHandle) -> HashMap<String, ModelDownloadState> {
+fn load_states(app: &impl AppContext) -> HashMap<String, ModelDownloadState> {
     let path = get_states_path(app);
     if let Ok(mut file) = fs::File::open(&path) {
         let mut contents = String::new();
@@ -131,7 +148,7 @@ fn load_states(app: &AppHandle) -> HashMap<String, ModelDownloadState> {
     HashMap::new()
 }
 
-fn save_states(app: &AppHandle, states: &HashMap<String, ModelDownloadState>) {
+fn save_states(app: &impl AppContext, states: &HashMap<String, ModelDownloadState>) {
     let path = get_states_path(app);
     if let Ok(json) = serde_json::to_string_pretty(states) {
         if let Ok(mut file) = fs::File::create(&path) {
@@ -141,8 +158,8 @@ fn save_states(app: &AppHandle, states: &HashMap<String, ModelDownloadState>) {
 }
 
 /// Helper to emit model state updates to the frontend.
-fn emit_model_states(app: &AppHandle, states: &HashMap<String, ModelDownloadState>) {
-    let _ = app.emit(
+fn emit_model_states(app: &impl AppContext, states: &HashMap<String, ModelDownloadState>) {
+    app.emit_event(
         "model-states-updated",
         serde_json::json!({ "states": states }),
     );
@@ -152,8 +169,13 @@ fn emit_model_states(app: &AppHandle, states: &HashMap<String, ModelDownloadStat
 
 #[tauri::command]
 pub fn list_models(app: AppHandle) -> Result<Vec<String>, String> {
-    println!("[list_models] called");
-    let models_dir = get_models_dir(&app);
+    Ok(installed_models(&app))
+}
+
+/// Returns the file names of all models in the models directory.
+pub fn installed_models(app: &impl AppContext) -> Vec<String> {
+    trace!("[list_models] called");
+    let models_dir = get_models_dir(app);
     let mut models = Vec::new();
     if let Ok(entries) = std::fs::read_dir(models_dir) {
         for entry in entries.flatten() {
@@ -165,15 +187,20 @@ pub fn list_models(app: AppHandle) -> Result<Vec<String>, String> {
             }
         }
     }
-    println!("[list_models] returning models: {:?}", models);
-    Ok(models)
+    trace!("[list_models] returning models: {:?}", models);
+    models
 }
 
 #[tauri::command]
 pub fn is_model_resumable(app: AppHandle, model_name: String) -> Result<(bool, u64), String> {
-    let models_dir = get_models_dir(&app);
-    let part_path = models_dir.join(format!("{}.part", &model_name));
-    let dest_path = models_dir.join(&model_name);
+    Ok(model_resume_info(&app, &model_name))
+}
+
+/// Returns whether a partial or complete download exists for the model, and its size in bytes.
+pub fn model_resume_info(app: &impl AppContext, model_name: &str) -> (bool, u64) {
+    let models_dir = get_models_dir(app);
+    let part_path = models_dir.join(format!("{}.part", model_name));
+    let dest_path = models_dir.join(model_name);
     // Prefer .part file for resumability
     let path = if part_path.exists() {
         part_path
@@ -183,18 +210,24 @@ pub fn is_model_resumable(app: AppHandle, model_name: String) -> Result<(bool, u
     if path.exists() {
         if let Ok(metadata) = std::fs::metadata(&path) {
             let size = metadata.len();
-            return Ok((size > 0, size));
+            return (size > 0, size);
         }
     }
-    Ok((false, 0))
+    (false, 0)
 }
 
 #[tauri::command]
 pub fn pause_model_download(app: AppHandle, model_name: String) -> Result<(), String> {
+    pause_download(&app, &model_name);
+    Ok(())
+}
+
+/// Marks a model download as paused; the running download stops at its next chunk.
+pub fn pause_download(app: &impl AppContext, model_name: &str) {
     let mut states = MODEL_STATES.lock().unwrap();
     let mut last_update_map = LAST_STATE_UPDATE.lock().unwrap();
     let now = Instant::now();
-    let should_update = match last_update_map.entry(model_name.clone()) {
+    let should_update = match last_update_map.entry(model_name.to_string()) {
         Entry::Occupied(mut e) => {
             let last = e.get_mut();
             if now.duration_since(*last) >= Duration::from_secs(1) {
@@ -209,19 +242,23 @@ pub fn pause_model_download(app: AppHandle, model_name: String) -> Result<(), St
             true
         }
     };
-    if let Some(state) = states.get_mut(&model_name) {
+    if let Some(state) = states.get_mut(model_name) {
         state.status = DownloadStatus::Paused.to_string();
     }
     if should_update {
-        save_states(&app, &states);
+        save_states(app, &states);
         // Emit updated states
-        emit_model_states(&app, &states);
+        emit_model_states(app, &states);
     }
-    Ok(())
 }
 
 #[tauri::command]
 pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), String> {
+    fetch_model(app, model_name).await
+}
+
+/// Downloads (or resumes downloading) a model into the models directory and verifies it.
+pub async fn fetch_model<C: AppContext>(app: C, model_name: String) -> Result<(), String> {
     {
         // Synchronously initialize model states before any .await
         init_model_states(&app);
@@ -237,7 +274,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
             }
         }
     }
-    println!("[download_model] ENTRY: model_name={}", model_name);
+    trace!("[download_model] ENTRY: model_name={}", model_name);
     use futures_util::StreamExt;
     use reqwest::Client;
     use std::fs;
@@ -252,7 +289,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
     );
     let models_dir = get_models_dir(&app);
     if !models_dir.exists() {
-        println!(
+        trace!(
             "[download_model] models_dir does not exist, creating: {:?}",
             models_dir
         );
@@ -271,7 +308,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 );
                 save_states(&app, &states);
             }
-            println!("[download_model] ERROR: Failed to create models dir: {}", e);
+            trace!("[download_model] ERROR: Failed to create models dir: {}", e);
             return Err(format!("Failed to create models dir: {}", e));
         }
     }
@@ -347,9 +384,10 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 file_size = metadata.len();
                 downloaded = file_size;
                 file_exists = true;
-                println!(
+                trace!(
                     "[download_model] Found partial file: {:?} ({} bytes)",
-                    part_path, file_size
+                    part_path,
+                    file_size
                 );
                 // Make an async HEAD request to get the server's file size
                 let client = Client::new();
@@ -366,7 +404,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 if server_file_size > 0 {
                     if file_size > server_file_size {
                         // Local file is too large, delete and start over
-                        println!("[download_model] Local .part file is larger than server file size ({} > {}), deleting and starting over", file_size, server_file_size);
+                        trace!("[download_model] Local .part file is larger than server file size ({} > {}), deleting and starting over", file_size, server_file_size);
                         let _ = fs::remove_file(&part_path);
                         file_size = 0;
                         downloaded = 0;
@@ -420,7 +458,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                     // else: file_size < server_file_size, resume as normal
                 } else {
                     // Could not determine server file size
-                    println!("[download_model] WARNING: Could not determine server file size. Proceeding to download from scratch.");
+                    trace!("[download_model] WARNING: Could not determine server file size. Proceeding to download from scratch.");
                     // Optionally, you could choose to resume anyway, but safest is to start over
                     let _ = fs::remove_file(&part_path);
                     file_size = 0;
@@ -433,14 +471,14 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
         let mut req = client.get(&url);
         if file_exists && file_size > 0 {
             req = req.header("Range", format!("bytes={}-", file_size));
-            println!("[download_model] Resuming download from byte {}", file_size);
+            trace!("[download_model] Resuming download from byte {}", file_size);
         }
         let resp = match req.send().await {
             Ok(r) => {
-                println!("[download_model] HTTP status: {}", r.status());
+                trace!("[download_model] HTTP status: {}", r.status());
                 if r.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
                     // 416 error, delete .part file and restart
-                    println!("[download_model] HTTP 416 Range Not Satisfiable, deleting .part file and starting over");
+                    trace!("[download_model] HTTP 416 Range Not Satisfiable, deleting .part file and starting over");
                     let _ = fs::remove_file(&part_path);
                     // continue the loop to retry from scratch
                     continue;
@@ -462,7 +500,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                     );
                     save_states(&app, &states);
                 }
-                println!("[download_model] ERROR: Failed to download: {}", e);
+                trace!("[download_model] ERROR: Failed to download: {}", e);
                 return Err(format!("Failed to download: {}", e));
             }
         };
@@ -475,17 +513,20 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
             Some(ref range) => {
                 let s = range.split('/').nth(1).unwrap_or("0");
                 let parsed = s.parse::<u64>().unwrap_or(0) + file_size;
-                println!(
+                trace!(
                     "[download_model] Content-Range: {} => total_size={} (file_size={})",
-                    range, parsed, file_size
+                    range,
+                    parsed,
+                    file_size
                 );
                 parsed
             }
             None => {
                 let len = resp.content_length().unwrap_or(0) + file_size;
-                println!(
+                trace!(
                     "[download_model] No Content-Range, total_size={} (file_size={})",
-                    len, file_size
+                    len,
+                    file_size
                 );
                 len
             }
@@ -498,10 +539,10 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 .await
                 .map_err(|e| format!("Failed to open file for append: {}", e))?;
             f.seek(SeekFrom::End(0)).await.ok();
-            println!("[download_model] Opened file for append: {:?}", part_path);
+            trace!("[download_model] Opened file for append: {:?}", part_path);
             f
         } else {
-            println!("[download_model] Creating new file: {:?}", part_path);
+            trace!("[download_model] Creating new file: {:?}", part_path);
             async_fs::File::create(&part_path)
                 .await
                 .map_err(|e| format!("Failed to create file: {}", e))?
@@ -524,7 +565,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 should_break
             }; // lock released here
             if paused_or_removing {
-                println!(
+                trace!(
                     "[download_model] Paused or removing at {} bytes",
                     downloaded
                 );
@@ -548,7 +589,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                         );
                         save_states(&app, &states);
                     }
-                    println!("[download_model] ERROR: Failed to read chunk: {}", e);
+                    trace!("[download_model] ERROR: Failed to read chunk: {}", e);
                     return Err(format!("Failed to read chunk: {}", e));
                 }
             };
@@ -569,7 +610,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                     );
                     save_states(&app, &states);
                 }
-                println!("[download_model] ERROR: Failed to write file: {}", e);
+                trace!("[download_model] ERROR: Failed to write file: {}", e);
                 return Err(format!("Failed to write file: {}", e));
             }
             downloaded += chunk_size as u64;
@@ -595,9 +636,11 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                     emit_model_states(&app, &states);
                 }
                 last_emit = std::time::Instant::now();
-                println!(
+                trace!(
                     "[download_model] Progress: {}% ({} / {})",
-                    progress, downloaded, total_size
+                    progress,
+                    downloaded,
+                    total_size
                 );
             }
         }
@@ -619,18 +662,18 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                     .await
                     {
                         Ok(Ok(())) => {
-                            println!(
+                            trace!(
                                 "[download_model] Model finalized and renamed successfully: {}",
                                 model_name
                             );
                             Ok(())
                         }
                         Ok(Err(e)) => {
-                            println!("[download_model] ERROR during finalization: {}", e);
+                            trace!("[download_model] ERROR during finalization: {}", e);
                             Err(e)
                         }
                         Err(e) => {
-                            println!("[download_model] ERROR: spawn_blocking failed: {}", e);
+                            trace!("[download_model] ERROR: spawn_blocking failed: {}", e);
                             Err(format!("spawn_blocking failed: {}", e))
                         }
                     };
@@ -639,17 +682,17 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
                 // After successful rename, ensure .part file is gone
                 if part_path.exists() {
                     if let Err(remove_err) = std::fs::remove_file(&part_path) {
-                        println!("[download_model] WARNING: .part file still existed after rename and could not be removed: {}", remove_err);
+                        trace!("[download_model] WARNING: .part file still existed after rename and could not be removed: {}", remove_err);
                     } else {
-                        println!("[download_model] .part file removed after rename")
+                        trace!("[download_model] .part file removed after rename")
                     }
                 }
                 // FINAL SAFETY: If .bin exists and .part still exists, remove .part
                 if dest_path.exists() && part_path.exists() {
                     if let Err(remove_err) = std::fs::remove_file(&part_path) {
-                        println!("[download_model] FINAL WARNING: .part file still existed after download and could not be removed: {}", remove_err);
+                        trace!("[download_model] FINAL WARNING: .part file still existed after download and could not be removed: {}", remove_err);
                     } else {
-                        println!("[download_model] FINAL: .part file removed after download");
+                        trace!("[download_model] FINAL: .part file removed after download");
                     }
                 }
             }
@@ -703,7 +746,7 @@ pub async fn download_model(app: AppHandle, model_name: String) -> Result<(), St
             };
             return result;
         }
-        println!("[download_model] EXIT: model_name={}", model_name);
+        trace!("[download_model] EXIT: model_name={}", model_name);
         return Ok(());
     }
 }
@@ -722,9 +765,14 @@ fn human_readable_size(bytes: u64) -> String {
 
 #[tauri::command]
 pub fn list_remote_models(app: AppHandle) -> Vec<RemoteModel> {
-    println!("[list_remote_models] called");
+    remote_models(&app)
+}
+
+/// Returns the models available for download, fetching their sizes when unknown.
+pub fn remote_models(app: &impl AppContext) -> Vec<RemoteModel> {
+    trace!("[list_remote_models] called");
     // Ensure model states are loaded
-    init_model_states(&app);
+    init_model_states(app);
     let mut states = MODEL_STATES.lock().unwrap();
     let model_urls = vec![
         (
@@ -805,18 +853,23 @@ pub fn list_remote_models(app: AppHandle) -> Vec<RemoteModel> {
         })
         .collect();
     if updated {
-        save_states(&app, &states);
+        save_states(app, &states);
     }
     models
 }
 
 #[tauri::command]
 pub fn remove_model(app: AppHandle, model_name: String) -> Result<(), String> {
-    init_model_states(&app);
+    delete_model(&app, &model_name)
+}
+
+/// Deletes a model and any partial download of it.
+pub fn delete_model(app: &impl AppContext, model_name: &str) -> Result<(), String> {
+    init_model_states(app);
     // Set state to removing
     let state_to_clone = {
         let states = MODEL_STATES.lock().unwrap();
-        if let Some(state) = states.get(&model_name) {
+        if let Some(state) = states.get(model_name) {
             if DownloadStatus::from_str(&state.status) == DownloadStatus::Downloading {
                 Some(state.clone())
             } else {
@@ -829,17 +882,17 @@ pub fn remove_model(app: AppHandle, model_name: String) -> Result<(), String> {
     if let Some(state) = state_to_clone {
         let mut states = MODEL_STATES.lock().unwrap();
         states.insert(
-            model_name.clone(),
+            model_name.to_string(),
             ModelDownloadState {
                 status: DownloadStatus::Removing.to_string(),
                 ..state
             },
         );
-        save_states(&app, &states);
+        save_states(app, &states);
     }
-    let models_dir = get_models_dir(&app);
-    let model_path = models_dir.join(&model_name);
-    let part_path = models_dir.join(format!("{}.part", &model_name));
+    let models_dir = get_models_dir(app);
+    let model_path = models_dir.join(model_name);
+    let part_path = models_dir.join(format!("{}.part", model_name));
     let mut removed = false;
     if model_path.exists() {
         if let Ok(_) = std::fs::remove_file(&model_path) {
@@ -853,27 +906,32 @@ pub fn remove_model(app: AppHandle, model_name: String) -> Result<(), String> {
     }
     if removed {
         let mut states = MODEL_STATES.lock().unwrap();
-        states.remove(&model_name);
-        save_states(&app, &states);
+        states.remove(model_name);
+        save_states(app, &states);
         return Ok(());
     } else {
         let mut states = MODEL_STATES.lock().unwrap();
-        if let Some(state) = states.get_mut(&model_name) {
+        if let Some(state) = states.get_mut(model_name) {
             state.status = DownloadStatus::Error.to_string();
             state.error = Some(format!("Failed to remove model: not found"));
         }
-        save_states(&app, &states);
+        save_states(app, &states);
         return Err(format!("Failed to remove model: not found"));
     }
 }
 
 #[tauri::command]
 pub fn get_model_states(app: AppHandle) -> Result<HashMap<String, ModelDownloadState>, String> {
-    init_model_states(&app);
+    Ok(model_states(&app))
+}
+
+/// Returns the download state of every known model and emits it to listeners.
+pub fn model_states(app: &impl AppContext) -> HashMap<String, ModelDownloadState> {
+    init_model_states(app);
     let states = MODEL_STATES.lock().unwrap();
     // Emit updated states on load
-    emit_model_states(&app, &states);
-    Ok(states.clone())
+    emit_model_states(app, &states);
+    states.clone()
 }
 
 /// Fetch and parse the Hugging Face README for model SHA256s.
@@ -942,7 +1000,7 @@ fn compute_file_sha1(path: &std::path::Path) -> Result<String, String> {
 /// Finalize a model download: verify checksum and rename .part to .bin if valid.
 /// Updates state accordingly. Returns Ok(()) if successful, Err(reason) otherwise.
 fn finalize_model_download(
-    app: &AppHandle,
+    app: &impl AppContext,
     model_name: &str,
     part_path: &std::path::Path,
     dest_path: &std::path::Path,
@@ -955,7 +1013,7 @@ fn finalize_model_download(
     if actual_sha == expected_sha {
         std::fs::rename(part_path, dest_path)
             .map_err(|e| format!("Failed to rename .part to .bin: {}", e))?;
-        println!(
+        trace!(
             "[finalize_model_download] Renamed .part to .bin for {} (SHA1 OK)",
             model_name
         );
@@ -979,9 +1037,11 @@ fn finalize_model_download(
         emit_model_states(app, &states);
         Ok(())
     } else {
-        println!(
+        trace!(
             "[finalize_model_download] ERROR: SHA1 mismatch for {}: expected {}, got {}",
-            model_name, expected_sha, actual_sha
+            model_name,
+            expected_sha,
+            actual_sha
         );
         let mut states = MODEL_STATES.lock().unwrap();
         states.insert(
@@ -999,3 +1059,19 @@ fn finalize_model_download(
         Err("SHA1 checksum mismatch after download".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+
+    #[test]
+    fn installed_models_lists_files_in_the_models_dir() {
+        let app = PlainContext::temp();
+        assert!(installed_models(&app).is_empty());
+        let models_dir = get_models_dir(&app);
+        fs::create_dir_all(models_dir.join("partial")).unwrap();
+        fs::write(models_dir.join("ggml-base.bin"), b"model").unwrap();
+        assert_eq!(installed_models(&app), vec!["ggml-base.bin".to_string()]);
+    }
+}
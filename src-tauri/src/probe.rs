@@ -0,0 +1,380 @@
+//! Media probing: reads duration, streams and codec details from `ffmpeg -i` output.
+
+use crate::context::AppContext;
+use crate::ffmpeg::resolve_ffmpeg_path;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::AppHandle;
+
+/// Kind of a stream inside a media file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamKind {
+    Audio,
+    Video,
+    Subtitle,
+    Data,
+    Attachment,
+    Other,
+}
+
+/// A single stream of a probed media file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaStream {
+    /// Index of the stream in the file, as used by `-map 0:N`.
+    pub index: u32,
+    /// Index among streams of the same kind, as used by `-map 0:a:N`.
+    pub type_index: u32,
+    pub kind: StreamKind,
+    pub codec: String,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    /// Sample rate in Hz, audio streams only.
+    pub sample_rate: Option<u32>,
+    /// Channel layout as reported by ffmpeg, e.g. `stereo` or `5.1(side)`.
+    pub channel_layout: Option<String>,
+    pub channels: Option<u32>,
+    pub default: bool,
+}
+
+/// Result of probing a media file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaInfo {
+    /// Container format names, e.g. `mov,mp4,m4a,3gp,3g2,mj2`.
+    pub format: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub bitrate_kbps: Option<u32>,
+    pub streams: Vec<MediaStream>,
+}
+
+/// Subtitle codecs ffmpeg can convert to SRT. Bitmap formats like PGS or DVD subtitles
+/// would need OCR.
+const TEXT_SUBTITLE_CODECS: [&str; 10] = [
+    "subrip",
+    "srt",
+    "ass",
+    "ssa",
+    "mov_text",
+    "webvtt",
+    "text",
+    "microdvd",
+    "subviewer",
+    "eia_608",
+];
+
+impl MediaInfo {
+    /// Returns the audio streams in file order.
+    pub fn audio_streams(&self) -> impl Iterator<Item = &MediaStream> {
+        self.streams.iter().filter(|s| s.kind == StreamKind::Audio)
+    }
+
+    /// Returns the subtitle streams that carry text and can be extracted.
+    pub fn text_subtitle_streams(&self) -> impl Iterator<Item = &MediaStream> {
+        self.streams.iter().filter(|s| {
+            s.kind == StreamKind::Subtitle && TEXT_SUBTITLE_CODECS.contains(&s.codec.as_str())
+        })
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.audio_streams().next().is_some()
+    }
+
+    /// Resolves a track selection to audio track indices (`0:a:N`). Explicit indices win over
+    /// a language tag; with neither, an empty list selects ffmpeg's default stream.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u32>)` - The selected audio track indices
+    /// * `Err(String)` - An index does not exist or no track has the requested language
+    pub fn select_audio_tracks(
+        &self,
+        indices: &[u32],
+        language: Option<&str>,
+    ) -> Result<Vec<u32>, String> {
+        let count = self.audio_streams().count() as u32;
+        if !indices.is_empty() {
+            if let Some(missing) = indices.iter().find(|&&i| i >= count) {
+                return Err(format!(
+                    "Audio track {} does not exist, the file has {} audio track(s)",
+                    missing, count
+                ));
+            }
+            return Ok(indices.to_vec());
+        }
+        match language {
+            Some(language) => {
+                let tracks: Vec<u32> = self
+                    .audio_streams()
+                    .filter(|s| {
+                        s.language
+                            .as_deref()
+                            .is_some_and(|l| l.eq_ignore_ascii_case(language))
+                    })
+                    .map(|s| s.type_index)
+                    .collect();
+                if tracks.is_empty() {
+                    return Err(format!("No audio track with language {}", language));
+                }
+                Ok(tracks)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+static INPUT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Input #0, (.+), from '").unwrap());
+
+static DURATION_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"Duration: (\d+):(\d{2}):(\d{2})\.(\d+)(?:.*bitrate: (\d+) kb/s)?").unwrap()
+});
+
+static STREAM_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*Stream #0:(\d+)(?:\[0x[0-9a-fA-F]+\])?(?:\(([^)]+)\))?: (\w+): (.*)$").unwrap()
+});
+
+static TITLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s{4,}title\s*: (.*)$").unwrap());
+
+/// Converts an ffmpeg channel layout to a channel count, e.g. `5.1(side)` to 6.
+fn channel_count(layout: &str) -> Option<u32> {
+    let layout = layout.split('(').next().unwrap_or(layout);
+    match layout {
+        "mono" => Some(1),
+        "stereo" | "downmix" => Some(2),
+        "quad" => Some(4),
+        _ => {
+            if let Some(n) = layout.strip_suffix(" channels") {
+                return n.trim().parse().ok();
+            }
+            layout
+                .split('.')
+                .map(|part| part.parse::<u32>().ok())
+                .sum::<Option<u32>>()
+        }
+    }
+}
+
+/// Parses the stream listing ffmpeg prints to stderr for `ffmpeg -i <input>`.
+pub fn parse_ffmpeg_probe(output: &str) -> MediaInfo {
+    let mut info = MediaInfo::default();
+    let mut kind_counts = [0u32; 6];
+    for line in output.lines() {
+        if let Some(caps) = INPUT_REGEX.captures(line) {
+            info.format = Some(caps[1].to_string());
+        } else if let Some(caps) = DURATION_REGEX.captures(line) {
+            let part = |i: usize| caps[i].parse::<u64>().unwrap_or(0);
+            // Fraction digits are centiseconds in practice; scale whatever precision is printed
+            let fraction = &caps[4];
+            let millis = format!("{:0<3}", &fraction[..fraction.len().min(3)])
+                .parse::<u64>()
+                .unwrap_or(0);
+            info.duration_ms = Some((part(1) * 3600 + part(2) * 60 + part(3)) * 1000 + millis);
+            info.bitrate_kbps = caps.get(5).and_then(|m| m.as_str().parse().ok());
+        } else if let Some(caps) = STREAM_REGEX.captures(line) {
+            let kind = match &caps[3] {
+                "Audio" => StreamKind::Audio,
+                "Video" => StreamKind::Video,
+                "Subtitle" => StreamKind::Subtitle,
+                "Data" => StreamKind::Data,
+                "Attachment" => StreamKind::Attachment,
+                _ => StreamKind::Other,
+            };
+            let details = &caps[4];
+            let parts: Vec<&str> = details.split(", ").collect();
+            let codec = parts
+                .first()
+                .and_then(|p| p.split_whitespace().next())
+                .unwrap_or("")
+                .to_string();
+            let mut sample_rate = None;
+            let mut channel_layout = None;
+            if kind == StreamKind::Audio {
+                if let Some(pos) = parts.iter().position(|p| p.ends_with(" Hz")) {
+                    sample_rate = parts[pos].trim_end_matches(" Hz").parse().ok();
+                    channel_layout = parts.get(pos + 1).map(|p| p.to_string());
+                }
+            }
+            let slot = &mut kind_counts[kind.clone() as usize];
+            info.streams.push(MediaStream {
+                index: caps[1].parse().unwrap_or(0),
+                type_index: *slot,
+                channels: channel_layout.as_deref().and_then(channel_count),
+                kind,
+                codec,
+                language: caps
+                    .get(2)
+                    .map(|m| m.as_str())
+                    .filter(|l| *l != "und")
+                    .map(str::to_string),
+                title: None,
+                sample_rate,
+                channel_layout,
+                default: details.contains("(default)"),
+            });
+            *slot += 1;
+        } else if let Some(caps) = TITLE_REGEX.captures(line) {
+            // Metadata lines after a stream line belong to that stream
+            if let Some(stream) = info.streams.last_mut() {
+                if stream.title.is_none() {
+                    stream.title = Some(caps[1].trim().to_string());
+                }
+            }
+        }
+    }
+    info
+}
+
+/// Probes a media file with the bundled ffmpeg.
+///
+/// # Arguments
+/// * `app` - Reference to the app context.
+/// * `input_path` - Path to the media file.
+///
+/// # Returns
+/// * `Ok(MediaInfo)` - Container, duration and stream details.
+/// * `Err(String)` - Error message if ffmpeg fails or cannot read the file.
+pub fn probe(app: &impl AppContext, input_path: &str) -> Result<MediaInfo, String> {
+    let ffmpeg_path = resolve_ffmpeg_path(app)?;
+    // Without an output file ffmpeg exits non-zero after printing the input details
+    let output = Command::new(&ffmpeg_path)
+        .args(["-hide_banner", "-i", input_path])
+        .output()
+        .map_err(|e| format!("[ffmpeg] Failed to start ffmpeg: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let info = parse_ffmpeg_probe(&stderr);
+    if info.format.is_none() {
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("unknown error");
+        return Err(format!(
+            "[ffmpeg] Failed to probe {}: {}",
+            input_path,
+            reason.trim()
+        ));
+    }
+    Ok(info)
+}
+
+/// Tauri command: Probe a media file for its duration, streams and codecs.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `input_path` - Path to the media file
+///
+/// # Returns
+/// * `Ok(MediaInfo)` - Container, duration and stream details
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub async fn probe_media(app: AppHandle, input_path: String) -> Result<MediaInfo, String> {
+    probe(&app, &input_path)
+}
+
+/// Tauri command: List the text subtitle streams embedded in a media file.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `input_path` - Path to the media file
+///
+/// # Returns
+/// * `Ok(Vec<MediaStream>)` - Extractable subtitle streams; pass `typeIndex` to
+///   `extract_subtitles`
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub async fn list_subtitle_streams(
+    app: AppHandle,
+    input_path: String,
+) -> Result<Vec<MediaStream>, String> {
+    Ok(probe(&app, &input_path)?
+        .text_subtitle_streams()
+        .cloned()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `ffmpeg -hide_banner -i` output of a screen recording without sound.
+    const VIDEO_ONLY: &str = "\
+Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'screen.mov':
+  Metadata:
+    major_brand     : qt
+    creation_time   : 2024-03-01T10:00:00.000000Z
+  Duration: 00:01:05.50, start: 0.000000, bitrate: 2450 kb/s
+  Stream #0:0[0x1](und): Video: h264 (High) (avc1 / 0x31637661), yuv420p(tv, bt709, progressive), 2880x1800, 2447 kb/s, 60 fps, 60 tbr, 6k tbn (default)
+      Metadata:
+        handler_name    : Core Media Video
+At least one output file must be specified";
+
+    /// A broadcast recording with a 5.1 main mix and a titled stereo commentary track.
+    const MULTI_AUDIO: &str = "\
+Input #0, mpegts, from 'recording.ts':
+  Duration: N/A, start: 1.400000, bitrate: N/A
+  Program 1
+    Metadata:
+      service_name    : Service01
+  Stream #0:0[0x100]: Video: h264 (Main) ([27][0][0][0] / 0x001B), yuv420p(tv, bt709, progressive), 1920x1080, 25 fps, 25 tbr, 90k tbn
+  Stream #0:1[0x2](eng): Audio: ac3 ([129][0][0][0] / 0x0081), 48000 Hz, 5.1(side), fltp, 448 kb/s
+  Stream #0:2[0x3](ger): Audio: aac (LC) ([15][0][0][0] / 0x000F), 44100 Hz, stereo, fltp, 128 kb/s
+      Metadata:
+        title           : Director's commentary
+  Stream #0:3[0x4](und): Audio: mp2 ([3][0][0][0] / 0x0003), 48000 Hz, mono, s16p, 64 kb/s
+At least one output file must be specified";
+
+    #[test]
+    fn video_only_files_have_no_audio() {
+        let info = parse_ffmpeg_probe(VIDEO_ONLY);
+        assert_eq!(info.format.as_deref(), Some("mov,mp4,m4a,3gp,3g2,mj2"));
+        assert_eq!(info.duration_ms, Some(65_500));
+        assert_eq!(info.bitrate_kbps, Some(2450));
+        assert_eq!(info.streams.len(), 1);
+        assert_eq!(info.streams[0].kind, StreamKind::Video);
+        assert_eq!(info.streams[0].language, None);
+        assert!(info.streams[0].default);
+        assert!(!info.has_audio());
+    }
+
+    #[test]
+    fn parses_multiple_audio_tracks() {
+        let info = parse_ffmpeg_probe(MULTI_AUDIO);
+        assert_eq!(info.format.as_deref(), Some("mpegts"));
+        assert_eq!(info.duration_ms, None);
+        assert_eq!(info.bitrate_kbps, None);
+        let audio: Vec<&MediaStream> = info.audio_streams().collect();
+        assert_eq!(audio.len(), 3);
+
+        assert_eq!(audio[0].index, 1);
+        assert_eq!(audio[0].type_index, 0);
+        assert_eq!(audio[0].codec, "ac3");
+        assert_eq!(audio[0].language.as_deref(), Some("eng"));
+        assert_eq!(audio[0].sample_rate, Some(48000));
+        assert_eq!(audio[0].channel_layout.as_deref(), Some("5.1(side)"));
+        assert_eq!(audio[0].channels, Some(6));
+
+        assert_eq!(audio[1].type_index, 1);
+        assert_eq!(audio[1].language.as_deref(), Some("ger"));
+        assert_eq!(audio[1].channels, Some(2));
+        assert_eq!(audio[1].title.as_deref(), Some("Director's commentary"));
+
+        assert_eq!(audio[2].language, None);
+        assert_eq!(audio[2].channels, Some(1));
+        assert_eq!(audio[2].title, None);
+
+        assert_eq!(info.select_audio_tracks(&[], Some("GER")).unwrap(), [1]);
+        assert!(info.select_audio_tracks(&[3], None).is_err());
+    }
+
+    #[test]
+    fn counts_channels_of_layouts() {
+        assert_eq!(channel_count("mono"), Some(1));
+        assert_eq!(channel_count("stereo"), Some(2));
+        assert_eq!(channel_count("5.1(side)"), Some(6));
+        assert_eq!(channel_count("7.1"), Some(8));
+        assert_eq!(channel_count("6 channels"), Some(6));
+        assert_eq!(channel_count("hexagonal"), None);
+    }
+}
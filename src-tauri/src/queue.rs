@@ -0,0 +1,471 @@
+//! Batch transcription queue with a concurrency limit and state persisted across restarts.
+
+use crate::context::AppContext;
+use crate::jobs::{cancel_transcription, CANCELLED_ERROR};
+use crate::whisper::{transcribe_job, Transcription, TranscriptionOptions};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+use tauri::AppHandle;
+
+/// Status of a single queued file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueItemStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A file waiting in, running in, or finished by the queue.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItem {
+    /// Job id, also accepted by `cancel_transcription` while the item is running.
+    pub id: String,
+    pub input_path: String,
+    pub model: String,
+    pub language: String,
+    #[serde(default)]
+    pub options: TranscriptionOptions,
+    pub status: QueueItemStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Persisted queue state.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueState {
+    /// Maximum number of files transcribed at the same time.
+    pub concurrency: usize,
+    pub items: Vec<QueueItem>,
+}
+
+impl Default for QueueState {
+    fn default() -> Self {
+        QueueState {
+            concurrency: 1,
+            items: Vec::new(),
+        }
+    }
+}
+
+// --- State Management ---
+
+static QUEUE: Lazy<Arc<Mutex<QueueState>>> =
+    Lazy::new(|| Arc::new(Mutex::new(QueueState::default())));
+
+static INIT: Once = Once::new();
+
+/// Loads the queue from disk once. Items that were running when the app exited are reset to
+/// pending so they are picked up again.
+fn init_queue(app: &impl AppContext) {
+    INIT.call_once(|| {
+        let mut queue = QUEUE.lock().unwrap();
+        *queue = load_queue(app);
+        for item in &mut queue.items {
+            if item.status == QueueItemStatus::Running {
+                item.status = QueueItemStatus::Pending;
+            }
+        }
+    });
+}
+
+fn get_queue_dir(app: &impl AppContext) -> PathBuf {
+    app.app_data_dir().join("queue")
+}
+
+fn get_queue_path(app: &impl AppContext) -> PathBuf {
+    get_queue_dir(app).join("queue_state.json")
+}
+
+/// Returns where the result of a queue item is stored. Item ids are UUIDs, anything else is
+/// rejected so an id can never point outside the queue dir.
+fn get_result_path(app: &impl AppContext, id: &str) -> Result<PathBuf, String> {
+    let id = uuid::Uuid::parse_str(id).map_err(|_| format!("Invalid queue item id: {}", id))?;
+    Ok(get_queue_dir(app).join(format!("{}.json", id)))
+}
+
+fn load_queue(app: &impl AppContext) -> QueueState {
+    fs::read_to_string(get_queue_path(app))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_queue(app: &impl AppContext, queue: &QueueState) {
+    let _ = fs::create_dir_all(get_queue_dir(app));
+    if let Ok(json) = serde_json::to_string_pretty(queue) {
+        let _ = fs::write(get_queue_path(app), json);
+    }
+}
+
+/// Helper to emit queue updates to the frontend.
+fn emit_queue(app: &impl AppContext, queue: &QueueState) {
+    app.emit_event("queue-updated", serde_json::json!({ "queue": queue }));
+}
+
+/// Starts pending items until the concurrency limit is reached.
+fn pump_queue(app: &impl AppContext) {
+    let mut queue = QUEUE.lock().unwrap();
+    let mut running = queue
+        .items
+        .iter()
+        .filter(|i| i.status == QueueItemStatus::Running)
+        .count();
+    let concurrency = queue.concurrency.max(1);
+    let mut started = false;
+    for item in queue.items.iter_mut() {
+        if running >= concurrency {
+            break;
+        }
+        if item.status != QueueItemStatus::Pending {
+            continue;
+        }
+        item.status = QueueItemStatus::Running;
+        item.error = None;
+        running += 1;
+        started = true;
+        let app = app.clone();
+        let item = item.clone();
+        std::thread::spawn(move || run_queue_item(app, item));
+    }
+    if started {
+        save_queue(app, &queue);
+        emit_queue(app, &queue);
+    }
+}
+
+/// Transcribes a single item, stores its result and starts the next pending item.
+fn run_queue_item<C: AppContext>(app: C, item: QueueItem) {
+    let result = transcribe_job(
+        &app,
+        &item.id,
+        &item.input_path,
+        &item.model,
+        &item.language,
+        &item.options,
+    );
+    finish_item(&app, &item.id, result);
+    pump_queue(&app);
+}
+
+/// Records the outcome of a finished item and writes its result file. Both happen under the
+/// queue lock, and only while the item is still queued: `remove_item` deletes the result file
+/// under the same lock, so a result written afterwards would never be cleaned up.
+fn finish_item(app: &impl AppContext, id: &str, result: Result<Transcription, String>) {
+    let mut queue = QUEUE.lock().unwrap();
+    // The item may have been removed while it was running
+    let Some(entry) = queue.items.iter_mut().find(|i| i.id == id) else {
+        return;
+    };
+    let result = result.and_then(|transcription| {
+        let json = serde_json::to_string(&transcription)
+            .map_err(|e| format!("Failed to serialize transcription: {}", e))?;
+        fs::create_dir_all(get_queue_dir(app))
+            .map_err(|e| format!("Failed to save transcription: {}", e))?;
+        fs::write(get_result_path(app, id)?, json)
+            .map_err(|e| format!("Failed to save transcription: {}", e))
+    });
+    match result {
+        Ok(()) => entry.status = QueueItemStatus::Completed,
+        Err(e) if e == CANCELLED_ERROR => entry.status = QueueItemStatus::Cancelled,
+        Err(e) => {
+            entry.status = QueueItemStatus::Failed;
+            entry.error = Some(e);
+        }
+    }
+    save_queue(app, &queue);
+    emit_queue(app, &queue);
+}
+
+/// Resumes pending items from a previous session. Called once on app startup.
+pub fn resume_queue(app: &impl AppContext) {
+    init_queue(app);
+    pump_queue(app);
+}
+
+/// Adds files to the queue and starts them as capacity allows, returning the new items.
+pub fn enqueue(
+    app: &impl AppContext,
+    input_paths: Vec<String>,
+    model: &str,
+    language: &str,
+    options: &TranscriptionOptions,
+) -> Vec<QueueItem> {
+    init_queue(app);
+    let items: Vec<QueueItem> = input_paths
+        .into_iter()
+        .map(|input_path| QueueItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            input_path,
+            model: model.to_string(),
+            language: language.to_string(),
+            options: options.clone(),
+            status: QueueItemStatus::Pending,
+            error: None,
+        })
+        .collect();
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        queue.items.extend(items.iter().cloned());
+        save_queue(app, &queue);
+        emit_queue(app, &queue);
+    }
+    pump_queue(app);
+    items
+}
+
+/// Returns a snapshot of the queue.
+pub fn queue_snapshot(app: &impl AppContext) -> QueueState {
+    init_queue(app);
+    QUEUE.lock().unwrap().clone()
+}
+
+/// Changes how many files are transcribed at the same time.
+pub fn set_concurrency(app: &impl AppContext, concurrency: usize) -> Result<(), String> {
+    if concurrency == 0 {
+        return Err("Concurrency must be at least 1".to_string());
+    }
+    init_queue(app);
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        queue.concurrency = concurrency;
+        save_queue(app, &queue);
+        emit_queue(app, &queue);
+    }
+    pump_queue(app);
+    Ok(())
+}
+
+/// Moves a failed or cancelled item back to pending.
+pub fn retry_item(app: &impl AppContext, id: &str) -> Result<(), String> {
+    init_queue(app);
+    {
+        let mut queue = QUEUE.lock().unwrap();
+        let item = queue
+            .items
+            .iter_mut()
+            .find(|i| i.id == id)
+            .ok_or_else(|| format!("Queue item {} not found", id))?;
+        if !matches!(
+            item.status,
+            QueueItemStatus::Failed | QueueItemStatus::Cancelled
+        ) {
+            return Err("Only failed or cancelled items can be retried".to_string());
+        }
+        item.status = QueueItemStatus::Pending;
+        item.error = None;
+        save_queue(app, &queue);
+        emit_queue(app, &queue);
+    }
+    pump_queue(app);
+    Ok(())
+}
+
+/// Removes an item from the queue, cancelling it first if it is running.
+pub fn remove_item(app: &impl AppContext, id: &str) -> Result<(), String> {
+    init_queue(app);
+    let mut queue = QUEUE.lock().unwrap();
+    let item = queue
+        .items
+        .iter()
+        .find(|i| i.id == id)
+        .ok_or_else(|| format!("Queue item {} not found", id))?;
+    if item.status == QueueItemStatus::Running {
+        let _ = cancel_transcription(id.to_string());
+    }
+    queue.items.retain(|i| i.id != id);
+    if let Ok(path) = get_result_path(app, id) {
+        let _ = fs::remove_file(path);
+    }
+    save_queue(app, &queue);
+    emit_queue(app, &queue);
+    Ok(())
+}
+
+/// Removes all completed, failed and cancelled items along with their stored results.
+pub fn clear_finished(app: &impl AppContext) {
+    init_queue(app);
+    let mut queue = QUEUE.lock().unwrap();
+    let (active, finished): (Vec<QueueItem>, Vec<QueueItem>) =
+        queue.items.drain(..).partition(|i| {
+            matches!(
+                i.status,
+                QueueItemStatus::Pending | QueueItemStatus::Running
+            )
+        });
+    for path in finished
+        .iter()
+        .filter_map(|i| get_result_path(app, &i.id).ok())
+    {
+        let _ = fs::remove_file(path);
+    }
+    queue.items = active;
+    save_queue(app, &queue);
+    emit_queue(app, &queue);
+}
+
+/// Loads the stored transcription of a completed item in the queue.
+pub fn load_result(app: &impl AppContext, id: &str) -> Result<Transcription, String> {
+    init_queue(app);
+    let completed = QUEUE
+        .lock()
+        .unwrap()
+        .items
+        .iter()
+        .any(|i| i.id == id && i.status == QueueItemStatus::Completed);
+    if !completed {
+        return Err(format!("Queue item {} has no result", id));
+    }
+    let contents = fs::read_to_string(get_result_path(app, id)?)
+        .map_err(|e| format!("No result for queue item {}: {}", id, e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse result: {}", e))
+}
+
+// --- Queue Commands ---
+
+/// Tauri command: Add files to the queue and start them as capacity allows.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `input_paths` - Paths of the files to transcribe
+/// * `model` - Model name used for every file
+/// * `language` - Language code used for every file
+/// * `options` - Optional transcription settings used for every file
+///
+/// # Returns
+/// * `Ok(Vec<QueueItem>)` - The newly queued items
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn enqueue_transcriptions(
+    app: AppHandle,
+    input_paths: Vec<String>,
+    model: String,
+    language: String,
+    options: Option<TranscriptionOptions>,
+) -> Result<Vec<QueueItem>, String> {
+    Ok(enqueue(
+        &app,
+        input_paths,
+        &model,
+        &language,
+        &options.unwrap_or_default(),
+    ))
+}
+
+/// Tauri command: Return the current queue state.
+#[tauri::command]
+pub fn get_queue(app: AppHandle) -> Result<QueueState, String> {
+    Ok(queue_snapshot(&app))
+}
+
+/// Tauri command: Change how many files are transcribed at the same time.
+#[tauri::command]
+pub fn set_queue_concurrency(app: AppHandle, concurrency: usize) -> Result<(), String> {
+    set_concurrency(&app, concurrency)
+}
+
+/// Tauri command: Move a failed or cancelled item back to pending.
+#[tauri::command]
+pub fn retry_queue_item(app: AppHandle, id: String) -> Result<(), String> {
+    retry_item(&app, &id)
+}
+
+/// Tauri command: Remove an item from the queue, cancelling it first if it is running.
+#[tauri::command]
+pub fn remove_queue_item(app: AppHandle, id: String) -> Result<(), String> {
+    remove_item(&app, &id)
+}
+
+/// Tauri command: Remove all completed, failed and cancelled items.
+#[tauri::command]
+pub fn clear_finished_queue_items(app: AppHandle) -> Result<(), String> {
+    clear_finished(&app);
+    Ok(())
+}
+
+/// Tauri command: Load the transcription of a completed queue item.
+#[tauri::command]
+pub fn get_queue_result(app: AppHandle, id: String) -> Result<Transcription, String> {
+    load_result(&app, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+
+    #[test]
+    fn result_paths_only_accept_uuids() {
+        let app = PlainContext::temp();
+        let id = uuid::Uuid::new_v4().to_string();
+        assert_eq!(
+            get_result_path(&app, &id).unwrap(),
+            get_queue_dir(&app).join(format!("{}.json", id))
+        );
+        assert!(get_result_path(&app, "../../settings").is_err());
+        assert!(get_result_path(&app, "").is_err());
+    }
+
+    #[test]
+    fn results_are_only_loaded_for_completed_items() {
+        let app = PlainContext::temp();
+        let id = uuid::Uuid::new_v4().to_string();
+        fs::create_dir_all(get_queue_dir(&app)).unwrap();
+        fs::write(get_result_path(&app, &id).unwrap(), "{}").unwrap();
+        assert!(load_result(&app, &id).is_err());
+        assert!(load_result(&app, "../queue/queue_state").is_err());
+    }
+
+    fn transcription() -> Transcription {
+        Transcription {
+            language: Some("en".to_string()),
+            language_probability: None,
+            segments: Vec::new(),
+            translation: None,
+        }
+    }
+
+    fn running_item(id: &str) -> QueueItem {
+        QueueItem {
+            id: id.to_string(),
+            input_path: "talk.wav".to_string(),
+            model: "ggml-base.bin".to_string(),
+            language: "en".to_string(),
+            options: TranscriptionOptions::default(),
+            status: QueueItemStatus::Running,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn finished_items_store_their_result() {
+        let app = PlainContext::temp();
+        let id = uuid::Uuid::new_v4().to_string();
+        QUEUE.lock().unwrap().items.push(running_item(&id));
+        finish_item(&app, &id, Ok(transcription()));
+        assert!(get_result_path(&app, &id).unwrap().is_file());
+        let status = QUEUE
+            .lock()
+            .unwrap()
+            .items
+            .iter()
+            .find(|i| i.id == id)
+            .map(|i| i.status.clone());
+        assert_eq!(status, Some(QueueItemStatus::Completed));
+    }
+
+    #[test]
+    fn removed_items_leave_no_result_behind() {
+        let app = PlainContext::temp();
+        let id = uuid::Uuid::new_v4().to_string();
+        finish_item(&app, &id, Ok(transcription()));
+        assert!(!get_result_path(&app, &id).unwrap().exists());
+    }
+}
@@ -0,0 +1,68 @@
+//! JSON list files in the app data dir, shared by the preset and glossary stores.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Loads a JSON array from `path`, `what` names its contents in error messages.
+///
+/// # Returns
+/// * `Ok(Vec<T>)` - The items, empty if the file does not exist yet
+/// * `Err(String)` - The file exists but could not be read or parsed. Callers must not save
+///   over it then, or the user's data is lost.
+pub fn load_json_list<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Vec<T>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {} in {}: {}", what, path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", what, e)),
+    }
+}
+
+/// Writes `items` to `path` as a pretty-printed JSON array, creating its directory.
+pub fn save_json_list<T: Serialize>(path: &Path, items: &[T], what: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(items)
+        .map_err(|e| format!("Failed to serialize {}: {}", what, e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to save {}: {}", what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("captions-test-{}", uuid::Uuid::new_v4()))
+            .join("items.json")
+    }
+
+    #[test]
+    fn missing_file_is_an_empty_list() {
+        let items: Vec<String> = load_json_list(&temp_path(), "items").unwrap();
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let path = temp_path();
+        save_json_list(&path, &["a", "b"], "items").unwrap();
+        let items: Vec<String> = load_json_list(&path, "items").unwrap();
+        assert_eq!(items, ["a", "b"]);
+    }
+
+    #[test]
+    fn unreadable_file_is_an_error() {
+        let path = temp_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[\"a\", ").unwrap();
+        let error = load_json_list::<String>(&path, "items").unwrap_err();
+        assert!(error.starts_with("Failed to parse items in"), "{}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[\"a\", ");
+    }
+}
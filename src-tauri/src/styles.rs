@@ -0,0 +1,93 @@
+//! Persistent subtitle style presets used by the ASS exporter.
+
+use crate::context::AppContext;
+use crate::storage::{load_json_list, save_json_list};
+use crate::subtitles::AssStyle;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+fn get_presets_path(app: &impl AppContext) -> PathBuf {
+    app.app_data_dir().join("style_presets.json")
+}
+
+/// Loads saved presets, always including the built-in `Default` preset unless it was overridden.
+///
+/// # Returns
+/// * `Ok(Vec<AssStyle>)` - The presets, only `Default` if none were saved yet
+/// * `Err(String)` - The presets file exists but could not be read or parsed
+pub fn load_presets(app: &impl AppContext) -> Result<Vec<AssStyle>, String> {
+    let mut presets: Vec<AssStyle> = load_json_list(&get_presets_path(app), "style presets")?;
+    let default = AssStyle::default();
+    if !presets.iter().any(|p| p.name == default.name) {
+        presets.insert(0, default);
+    }
+    Ok(presets)
+}
+
+fn save_presets(app: &impl AppContext, presets: &[AssStyle]) -> Result<(), String> {
+    save_json_list(&get_presets_path(app), presets, "style presets")
+}
+
+/// Looks up a style preset by name.
+pub fn get_style_preset(app: &impl AppContext, name: &str) -> Result<AssStyle, String> {
+    load_presets(app)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Style preset '{}' not found", name))
+}
+
+/// Tauri command: List all style presets, including the built-in `Default`.
+#[tauri::command]
+pub fn list_style_presets(app: AppHandle) -> Result<Vec<AssStyle>, String> {
+    load_presets(&app)
+}
+
+/// Creates or replaces a style preset, keyed by its name. Fails without saving when the
+/// existing presets could not be loaded, so they are never overwritten.
+fn store_preset(app: &impl AppContext, preset: AssStyle) -> Result<(), String> {
+    preset.validate()?;
+    let mut presets = load_presets(app)?;
+    match presets.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    save_presets(app, &presets)
+}
+
+/// Tauri command: Create or replace a style preset, keyed by its name.
+#[tauri::command]
+pub fn save_style_preset(app: AppHandle, preset: AssStyle) -> Result<(), String> {
+    store_preset(&app, preset)
+}
+
+/// Tauri command: Delete a style preset. Deleting `Default` restores the built-in preset.
+#[tauri::command]
+pub fn delete_style_preset(app: AppHandle, name: String) -> Result<(), String> {
+    let mut presets = load_presets(&app)?;
+    let before = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == before {
+        return Err(format!("Style preset '{}' not found", name));
+    }
+    save_presets(&app, &presets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+
+    #[test]
+    fn presets_round_trip_through_the_data_dir() {
+        let app = PlainContext::temp();
+        assert_eq!(load_presets(&app).unwrap().len(), 1);
+        let preset = AssStyle {
+            name: "Large".to_string(),
+            font_size: 72,
+            ..AssStyle::default()
+        };
+        store_preset(&app, preset).unwrap();
+        assert_eq!(get_style_preset(&app, "Large").unwrap().font_size, 72);
+        assert!(get_style_preset(&app, "Default").is_ok());
+    }
+}
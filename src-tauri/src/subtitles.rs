@@ -1,39 +1,726 @@
 //! Utilities for working with subtitle formats and conversions.
+//!
+//! Every format is parsed into a [`SubtitleDocument`] and serialized back out from it,
+//! so exports and edits all operate on the same typed structure.
 
-use regex::Regex;
-use srtlib::Subtitles;
-use std::error::Error;
+use crate::styles::get_style_preset;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
 
-/// Extracts only the text from an SRT string, removing timestamps and sequence numbers.
-///
-/// # Arguments
-/// * `srt_content` - The SRT file content as a string.
-///
-/// # Returns
-/// * `Ok(String)` - The extracted subtitle text.
-/// * `Err(Box<dyn Error>)` - If parsing fails.
-fn srt_text_only(srt_content: &str) -> Result<String, Box<dyn Error>> {
-    let subs = Subtitles::parse_from_str(srt_content.to_string())?;
-    let mut result = String::new();
-    for sub in subs {
-        result.push_str(&sub.text);
-        result.push('\n');
+/// A single word with its own timing, when the transcription provides word-level data.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Word {
+    /// Start time in milliseconds.
+    pub start: u64,
+    /// End time in milliseconds.
+    pub end: u64,
+    pub text: String,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+/// A single timed subtitle cue, independent of the source format.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+    /// Optional cue identifier (SRT sequence number or WebVTT cue id).
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Start time in milliseconds.
+    pub start: u64,
+    /// End time in milliseconds.
+    pub end: u64,
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<String>,
+    #[serde(default)]
+    pub words: Vec<Word>,
+    /// Average token probability for the cue, between 0 and 1.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+impl Cue {
+    /// Creates a cue with only timing and text set.
+    pub fn new(start: u64, end: u64, text: impl Into<String>) -> Self {
+        Cue {
+            id: None,
+            start,
+            end,
+            text: text.into(),
+            speaker: None,
+            words: Vec::new(),
+            confidence: None,
+        }
+    }
+
+    /// Moves the cue and its words onto another timeline, e.g. adding an offset.
+    pub fn map_times(&mut self, map: impl Fn(u64) -> u64) {
+        self.start = map(self.start);
+        self.end = map(self.end);
+        for word in &mut self.words {
+            word.start = map(word.start);
+            word.end = map(word.end);
+        }
+    }
+
+    /// Applies replacement rules to the cue text and its words.
+    pub fn apply_replacements(&mut self, replacements: &Replacements) {
+        self.text = replacements.apply(&self.text);
+        self.words = replacements.apply_to_words(&self.words);
     }
-    Ok(result.trim().to_string())
 }
 
-/// Tauri command: Remove timestamps and sequence numbers from SRT content, returning only the text.
-#[tauri::command]
-pub fn filter_srt_timestamps(srt_content: String) -> Result<String, String> {
-    srt_text_only(&srt_content).map_err(|e| e.to_string())
+/// In-memory subtitle document shared by all parsers, serializers and editing operations.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleDocument {
+    pub cues: Vec<Cue>,
 }
 
-/// Helper to get the regex for parsing Whisper output lines.
-fn whisper_srt_regex() -> Regex {
-    Regex::new(
-        r"^\[(\d{2}):(\d{2}):(\d{2})\.(\d{3}) --> (\d{2}):(\d{2}):(\d{2})\.(\d{3})\]\s*(.*)$",
-    )
+/// Shared pattern for a single `HH:MM:SS.mmm` (Whisper/WebVTT) or `HH:MM:SS,mmm` (SRT) timestamp.
+/// Every format parser builds on this so the formats stay in sync.
+const TIMESTAMP_PATTERN: &str = r"(\d{2}):(\d{2}):(\d{2})[.,](\d{3})";
+
+/// Regex for parsing Whisper output lines, compiled once since it runs on every streamed line.
+static WHISPER_SRT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"^\[{ts} --> {ts}\]\s*(.*)$",
+        ts = TIMESTAMP_PATTERN
+    ))
     .expect("Failed to compile Whisper SRT regex")
+});
+
+/// Parses a single Whisper console output line (`[00:00:00.000 --> 00:00:03.440]   text`).
+pub fn parse_whisper_line(line: &str) -> Option<Cue> {
+    WHISPER_SRT_REGEX.captures(line).map(|caps| {
+        Cue::new(
+            captures_to_ms(&caps, 1),
+            captures_to_ms(&caps, 5),
+            caps[9].trim(),
+        )
+    })
+}
+
+/// Helper to get the regex for parsing SRT timing lines.
+/// Blank line between SRT blocks, which may still contain spaces or tabs.
+static SRT_BLOCK_SEPARATOR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\n[ \t]*\n").expect("Failed to compile SRT separator regex"));
+
+fn srt_timing_regex() -> Regex {
+    Regex::new(&format!(r"^{ts}\s*-->\s*{ts}", ts = TIMESTAMP_PATTERN))
+        .expect("Failed to compile SRT timing regex")
+}
+
+/// Converts the four timestamp capture groups starting at `first` into milliseconds.
+fn captures_to_ms(caps: &Captures, first: usize) -> u64 {
+    let part = |i: usize| caps[first + i].parse::<u64>().unwrap_or(0);
+    ((part(0) * 60 + part(1)) * 60 + part(2)) * 1000 + part(3)
+}
+
+/// Formats milliseconds as `HH:MM:SS<sep>mmm`.
+fn format_timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// A find-and-replace rule for cue text, e.g. fixing a misspelled product name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacementRule {
+    /// Text to find, case-insensitively and as a whole word. With `regex` it is a regular
+    /// expression matched as written, still case-insensitively unless it turns that off with
+    /// `(?-i)`.
+    pub pattern: String,
+    /// Text to put in place of each match. With `regex` it may refer to groups as `$1`.
+    pub replacement: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+/// A compiled replacement rule.
+struct CompiledRule {
+    regex: Regex,
+    replacement: String,
+    /// Expand `$1`-style group references in `replacement`.
+    expand: bool,
+}
+
+impl CompiledRule {
+    /// Returns the text to put in place of one match.
+    fn replace(&self, caps: &Captures) -> String {
+        let mut replaced = String::new();
+        if self.expand {
+            caps.expand(&self.replacement, &mut replaced);
+        } else {
+            replaced.push_str(&self.replacement);
+        }
+        match_case(&caps[0], &replaced)
+    }
+}
+
+/// Compiled replacement rules, applied in order.
+pub struct Replacements {
+    rules: Vec<CompiledRule>,
+}
+
+impl Replacements {
+    /// Compiles the rules, skipping rules without a pattern.
+    ///
+    /// # Returns
+    /// * `Ok(Replacements)` - The compiled rules
+    /// * `Err(String)` - A regex pattern does not compile
+    pub fn new(rules: &[ReplacementRule]) -> Result<Self, String> {
+        let word_boundary = |c: Option<char>| {
+            if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                r"\b"
+            } else {
+                ""
+            }
+        };
+        let rules = rules
+            .iter()
+            .filter(|rule| !rule.pattern.trim().is_empty())
+            .map(|rule| {
+                let pattern = if rule.regex {
+                    format!("(?i){}", rule.pattern)
+                } else {
+                    // \b only works next to word characters, e.g. not after `C++`
+                    let text = rule.pattern.trim();
+                    format!(
+                        "(?i){}{}{}",
+                        word_boundary(text.chars().next()),
+                        regex::escape(text),
+                        word_boundary(text.chars().last())
+                    )
+                };
+                Regex::new(&pattern)
+                    .map(|regex| CompiledRule {
+                        regex,
+                        replacement: rule.replacement.clone(),
+                        expand: rule.regex,
+                    })
+                    .map_err(|e| format!("Invalid pattern '{}': {}", rule.pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Replacements { rules })
+    }
+
+    /// Replaces every match in `text`, carrying the match's casing over to the replacement.
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            text = rule
+                .regex
+                .replace_all(&text, |caps: &Captures| rule.replace(caps))
+                .into_owned();
+        }
+        text
+    }
+
+    /// Applies the rules to timed words. The words are matched as one space-separated line
+    /// like the cue text, so a pattern spanning several words (`why sper` -> `Whisper`)
+    /// merges them into one word covering their combined timing. Words replaced by nothing
+    /// are dropped.
+    pub fn apply_to_words(&self, words: &[Word]) -> Vec<Word> {
+        let mut words = words.to_vec();
+        for rule in &self.rules {
+            let mut line = String::new();
+            let mut ranges = Vec::with_capacity(words.len());
+            for word in &words {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                ranges.push(line.len()..line.len() + word.text.len());
+                line.push_str(&word.text);
+            }
+            let matches: Vec<_> = rule
+                .regex
+                .captures_iter(&line)
+                .map(|caps| (caps.get(0).unwrap().range(), rule.replace(&caps)))
+                .collect();
+            if matches.is_empty() {
+                continue;
+            }
+            // Neighbouring words merge when a match covers the space between them
+            let spans_gap = |gap: usize| matches.iter().any(|(m, _)| m.start <= gap && gap < m.end);
+            let mut merged = Vec::with_capacity(words.len());
+            let mut first = 0;
+            for last in 0..words.len() {
+                if last + 1 < words.len() && spans_gap(ranges[last].end) {
+                    continue;
+                }
+                let (start, end) = (ranges[first].start, ranges[last].end);
+                let mut text = String::new();
+                let mut pos = start;
+                for (m, replaced) in matches
+                    .iter()
+                    .filter(|(m, _)| start <= m.start && m.end <= end)
+                {
+                    text.push_str(&line[pos..m.start]);
+                    text.push_str(replaced);
+                    pos = m.end;
+                }
+                text.push_str(&line[pos..end]);
+                let group = &words[first..=last];
+                let confidences: Vec<f32> = group.iter().filter_map(|w| w.confidence).collect();
+                first = last + 1;
+                let text = text.trim();
+                if text.is_empty() {
+                    continue;
+                }
+                merged.push(Word {
+                    start: group[0].start,
+                    end: group.iter().map(|w| w.end).max().unwrap_or(group[0].end),
+                    text: text.to_string(),
+                    confidence: (!confidences.is_empty())
+                        .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32),
+                });
+            }
+            words = merged;
+        }
+        words
+    }
+}
+
+/// Adapts an all-lowercase replacement to the casing of the text it replaces: shouted text
+/// stays uppercase and a capitalized word stays capitalized. Replacements with their own
+/// capitals, like `iPhone`, are kept as written.
+fn match_case(matched: &str, replacement: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    let capitalized = matched.chars().next().is_some_and(char::is_uppercase);
+    if capitalized && !replacement.chars().any(char::is_uppercase) {
+        let mut chars = replacement.chars();
+        return match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+    }
+    replacement.to_string()
+}
+
+/// Options for WebVTT export.
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VttOptions {
+    /// Emit a cue identifier line before every cue.
+    pub cue_ids: bool,
+    /// Free-form comments emitted as `NOTE` blocks after the header.
+    pub notes: Vec<String>,
+    /// Emit inline `<00:00:01.200>` timestamps before each word of cues that carry word timings.
+    pub word_timestamps: bool,
+}
+
+/// WebVTT forbids `-->` inside cue payloads and NOTE blocks, and blank lines end a block.
+fn sanitize_vtt_block(text: &str) -> String {
+    text.replace("-->", "->")
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escapes cue text for a WebVTT payload, where `&`, `<` and `>` start entities and tags.
+fn escape_vtt_text(text: &str) -> String {
+    sanitize_vtt_block(text)
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A named ASS style preset. Colours are CSS-style `#RRGGBB` or `#RRGGBBAA` strings.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssStyle {
+    pub name: String,
+    pub font_name: String,
+    pub font_size: u32,
+    pub primary_color: String,
+    pub secondary_color: String,
+    pub outline_color: String,
+    pub back_color: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub outline: f32,
+    pub shadow: f32,
+    /// Numpad-style alignment (1-9), 2 is bottom center.
+    pub alignment: u8,
+    pub margin_l: u32,
+    pub margin_r: u32,
+    pub margin_v: u32,
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        AssStyle {
+            name: "Default".to_string(),
+            font_name: "Arial".to_string(),
+            font_size: 64,
+            primary_color: "#FFFFFF".to_string(),
+            secondary_color: "#FFD700".to_string(),
+            outline_color: "#000000".to_string(),
+            back_color: "#00000080".to_string(),
+            bold: false,
+            italic: false,
+            outline: 3.0,
+            shadow: 1.0,
+            alignment: 2,
+            margin_l: 60,
+            margin_r: 60,
+            margin_v: 50,
+        }
+    }
+}
+
+impl AssStyle {
+    /// Checks that the preset can be rendered into a valid ASS style line.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.contains(',') {
+            return Err("Style name must be non-empty and must not contain commas".to_string());
+        }
+        if self.font_name.trim().is_empty() || self.font_name.contains(',') {
+            return Err("Font name must be non-empty and must not contain commas".to_string());
+        }
+        if !(1..=9).contains(&self.alignment) {
+            return Err(format!(
+                "Invalid alignment {}, expected 1-9",
+                self.alignment
+            ));
+        }
+        for colour in [
+            &self.primary_color,
+            &self.secondary_color,
+            &self.outline_color,
+            &self.back_color,
+        ] {
+            css_to_ass_colour(colour)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the `Style:` line for the `[V4+ Styles]` section.
+    fn to_ass_line(&self) -> Result<String, String> {
+        self.validate()?;
+        let flag = |b: bool| if b { -1 } else { 0 };
+        Ok(format!(
+            "Style: {},{},{},{},{},{},{},{},{},0,0,100,100,0,0,1,{},{},{},{},{},{},1",
+            self.name,
+            self.font_name,
+            self.font_size,
+            css_to_ass_colour(&self.primary_color)?,
+            css_to_ass_colour(&self.secondary_color)?,
+            css_to_ass_colour(&self.outline_color)?,
+            css_to_ass_colour(&self.back_color)?,
+            flag(self.bold),
+            flag(self.italic),
+            self.outline,
+            self.shadow,
+            self.alignment,
+            self.margin_l,
+            self.margin_r,
+            self.margin_v
+        ))
+    }
+}
+
+/// Converts `#RRGGBB` / `#RRGGBBAA` to ASS `&HAABBGGRR`, where ASS alpha 00 is opaque.
+fn css_to_ass_colour(colour: &str) -> Result<String, String> {
+    let hex = colour.trim().trim_start_matches('#');
+    let valid = (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(format!(
+            "Invalid colour '{}', expected #RRGGBB or #RRGGBBAA",
+            colour
+        ));
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    let alpha = if hex.len() == 8 { 255 - byte(6) } else { 0 };
+    Ok(format!(
+        "&H{:02X}{:02X}{:02X}{:02X}",
+        alpha,
+        byte(4),
+        byte(2),
+        byte(0)
+    ))
+}
+
+/// Script resolution the style sizes and margins are expressed in.
+const ASS_PLAY_RES: (u32, u32) = (1920, 1080);
+
+/// Formats milliseconds as an ASS `H:MM:SS.cc` timestamp.
+fn format_ass_timestamp(ms: u64) -> String {
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        (ms % 1000) / 10
+    )
+}
+
+/// Converts cue text to a single ASS dialogue line, using `\N` for line breaks. Braces are
+/// escaped so caption text is never read as override tags.
+fn escape_ass_text(text: &str) -> String {
+    text.trim()
+        .replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace("\r\n", "\n")
+        .replace('\n', "\\N")
+}
+
+/// Yields each word of a cue with its timing clamped into the cue and made monotonic.
+fn clamped_words(cue: &Cue) -> impl Iterator<Item = (u64, u64, &str)> {
+    let mut cursor = cue.start;
+    cue.words.iter().map(move |word| {
+        let start = word.start.clamp(cursor, cue.end);
+        let end = word.end.clamp(start, cue.end);
+        cursor = end;
+        (start, end, word.text.trim())
+    })
+}
+
+/// Builds ASS karaoke text, e.g. `{\k35}Hello {\k10}{\k42}world`. Durations are in centiseconds
+/// and computed from rounded absolute times so they do not drift over long cues.
+fn ass_karaoke_text(cue: &Cue) -> String {
+    let cs = |ms: u64| ms / 10;
+    let mut text = String::new();
+    let mut cursor = cue.start;
+    for (i, (start, end, word)) in clamped_words(cue).enumerate() {
+        if i > 0 {
+            text.push(' ');
+        }
+        if cs(start) > cs(cursor) {
+            text.push_str(&format!("{{\\k{}}}", cs(start) - cs(cursor)));
+        }
+        text.push_str(&format!(
+            "{{\\k{}}}{}",
+            cs(end) - cs(start),
+            escape_ass_text(word)
+        ));
+        cursor = end;
+    }
+    text
+}
+
+/// Builds WebVTT cue text with an inline timestamp tag before every word after the first,
+/// e.g. `Hello <00:00:01.200>world`.
+fn vtt_karaoke_text(cue: &Cue) -> String {
+    let mut text = String::new();
+    for (i, (start, _, word)) in clamped_words(cue).enumerate() {
+        if i > 0 {
+            text.push(' ');
+            if start > cue.start && start < cue.end {
+                text.push_str(&format!("<{}>", format_timestamp(start, '.')));
+            }
+        }
+        text.push_str(&escape_vtt_text(word));
+    }
+    text
+}
+
+impl SubtitleDocument {
+    /// Parses Whisper console output lines (`[00:00:00.000 --> 00:00:03.440]   text`).
+    pub fn from_whisper(whisper_output: &str) -> Self {
+        let cues = whisper_output
+            .lines()
+            .filter_map(parse_whisper_line)
+            .collect();
+        SubtitleDocument { cues }
+    }
+
+    /// Parses SRT content, keeping the sequence numbers as cue identifiers.
+    pub fn from_srt(srt_content: &str) -> Self {
+        let re = srt_timing_regex();
+        let normalized = srt_content.replace("\r\n", "\n");
+        let mut cues = Vec::new();
+        for block in SRT_BLOCK_SEPARATOR.split(&normalized) {
+            let lines: Vec<&str> = block.lines().collect();
+            let Some(timing_idx) = lines.iter().position(|l| re.is_match(l.trim())) else {
+                continue;
+            };
+            let caps = re.captures(lines[timing_idx].trim()).unwrap();
+            let mut cue = Cue::new(
+                captures_to_ms(&caps, 1),
+                captures_to_ms(&caps, 5),
+                lines[timing_idx + 1..].join("\n").trim(),
+            );
+            cue.id = timing_idx
+                .checked_sub(1)
+                .map(|i| lines[i].trim().to_string())
+                .filter(|id| !id.is_empty());
+            cues.push(cue);
+        }
+        SubtitleDocument { cues }
+    }
+
+    /// Returns only the cue text, one cue per line.
+    pub fn to_plain_text(&self) -> String {
+        self.cues
+            .iter()
+            .map(|c| c.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// Serializes the document as SRT, numbering cues sequentially.
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        for (i, cue) in self.cues.iter().enumerate() {
+            srt.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_timestamp(cue.start, ','),
+                format_timestamp(cue.end, ','),
+                cue.text
+            ));
+        }
+        srt.trim().to_string()
+    }
+
+    /// Serializes the document as WebVTT with a `WEBVTT` header, optional NOTE blocks and
+    /// cue identifiers. Speakers are written as `<v>` voice spans.
+    pub fn to_vtt(&self, options: &VttOptions) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        for note in &options.notes {
+            let note = sanitize_vtt_block(note);
+            if !note.is_empty() {
+                vtt.push_str(&format!("NOTE\n{}\n\n", note));
+            }
+        }
+        for (i, cue) in self.cues.iter().enumerate() {
+            if options.cue_ids {
+                let id = cue
+                    .id
+                    .as_deref()
+                    .map(sanitize_vtt_block)
+                    .filter(|id| !id.is_empty())
+                    .unwrap_or_else(|| (i + 1).to_string());
+                vtt.push_str(&id);
+                vtt.push('\n');
+            }
+            let text = if options.word_timestamps && !cue.words.is_empty() {
+                vtt_karaoke_text(cue)
+            } else {
+                escape_vtt_text(&cue.text)
+            };
+            let text = match &cue.speaker {
+                Some(speaker) => {
+                    format!(
+                        "<v {}>{}",
+                        escape_vtt_text(speaker).replace('\n', " "),
+                        text
+                    )
+                }
+                None => text,
+            };
+            vtt.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                format_timestamp(cue.start, '.'),
+                format_timestamp(cue.end, '.'),
+                text
+            ));
+        }
+        vtt.trim_end().to_string() + "\n"
+    }
+
+    /// Serializes the document as an ASS script using the given style for every dialogue line.
+    /// Speakers are written to the `Name` field. With `karaoke`, cues that carry word timings
+    /// get `\k` tags so each word fills from the secondary to the primary colour as it is spoken.
+    pub fn to_ass(&self, style: &AssStyle, karaoke: bool) -> Result<String, String> {
+        let mut ass = String::from("[Script Info]\n; Script generated by Captions\n");
+        ass.push_str("ScriptType: v4.00+\n");
+        ass.push_str(&format!(
+            "PlayResX: {}\nPlayResY: {}\n",
+            ASS_PLAY_RES.0, ASS_PLAY_RES.1
+        ));
+        ass.push_str("WrapStyle: 0\nScaledBorderAndShadow: yes\n\n");
+        ass.push_str("[V4+ Styles]\n");
+        ass.push_str("Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
+        ass.push_str(&style.to_ass_line()?);
+        ass.push_str("\n\n[Events]\n");
+        ass.push_str(
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        );
+        for cue in &self.cues {
+            ass.push_str(&format!(
+                "Dialogue: 0,{},{},{},{},0,0,0,,{}\n",
+                format_ass_timestamp(cue.start),
+                format_ass_timestamp(cue.end),
+                style.name,
+                cue.speaker.as_deref().unwrap_or("").replace(',', " "),
+                if karaoke && !cue.words.is_empty() {
+                    ass_karaoke_text(cue)
+                } else {
+                    escape_ass_text(&cue.text)
+                }
+            ));
+        }
+        Ok(ass)
+    }
+
+    /// Adds the text of `translation` as a second line to each cue. Translated cues are
+    /// matched by their midpoint, since both documents come from separate runs with their own
+    /// cue boundaries; a cue without a match keeps its text. Word timings are dropped as they
+    /// only cover the first line.
+    pub fn with_translation(&self, translation: &SubtitleDocument) -> SubtitleDocument {
+        let cues = self
+            .cues
+            .iter()
+            .map(|cue| {
+                let translated: Vec<&str> = translation
+                    .cues
+                    .iter()
+                    .filter(|t| (cue.start..cue.end).contains(&((t.start + t.end) / 2)))
+                    .map(|t| t.text.trim())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                let mut cue = cue.clone();
+                if !translated.is_empty() {
+                    cue.text = format!("{}\n{}", cue.text, translated.join(" "));
+                    cue.words.clear();
+                }
+                cue
+            })
+            .collect();
+        SubtitleDocument { cues }
+    }
+
+    /// Applies replacement rules to every cue.
+    pub fn apply_replacements(&mut self, replacements: &Replacements) {
+        for cue in &mut self.cues {
+            cue.apply_replacements(replacements);
+        }
+    }
+
+    /// Returns an error if the document has no cues, using `source` in the message.
+    pub fn require_cues(self, source: &str) -> Result<Self, String> {
+        if self.cues.is_empty() {
+            Err(format!("No valid lines found in {}", source))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Tauri command: Remove timestamps and sequence numbers from SRT content, returning only the text.
+#[tauri::command]
+pub fn filter_srt_timestamps(srt_content: String) -> Result<String, String> {
+    let doc = SubtitleDocument::from_srt(&srt_content).require_cues("SRT content")?;
+    Ok(doc.to_plain_text())
 }
 
 /// Converts Whisper output format to SRT string.
@@ -46,23 +733,307 @@ fn whisper_srt_regex() -> Regex {
 /// * `Err(String)` - If parsing fails or no valid lines are found.
 #[tauri::command]
 pub fn whisper_to_srt(whisper_output: String) -> Result<String, String> {
-    let re = whisper_srt_regex();
-    let mut srt = String::new();
-    let mut idx = 1;
-    for line in whisper_output.lines() {
-        if let Some(caps) = re.captures(line) {
-            let start = format!("{}:{}:{},{}", &caps[1], &caps[2], &caps[3], &caps[4]);
-            let end = format!("{}:{}:{},{}", &caps[5], &caps[6], &caps[7], &caps[8]);
-            // Convert . to , for SRT
-            let start = start.replace('.', ",");
-            let end = end.replace('.', ",");
-            let text = caps[9].trim();
-            srt.push_str(&format!("{}\n{} --> {}\n{}\n\n", idx, start, end, text));
-            idx += 1;
-        }
-    }
-    if srt.is_empty() {
-        return Err("No valid lines found in whisper output".to_string());
-    }
-    Ok(srt.trim().to_string())
+    let doc = SubtitleDocument::from_whisper(&whisper_output).require_cues("whisper output")?;
+    Ok(doc.to_srt())
+}
+
+/// Converts Whisper output format to a WebVTT string.
+///
+/// # Arguments
+/// * `whisper_output` - The output from Whisper as a string.
+/// * `options` - Optional cue identifier and NOTE settings.
+///
+/// # Returns
+/// * `Ok(String)` - The WebVTT formatted string.
+/// * `Err(String)` - If no valid lines are found.
+#[tauri::command]
+pub fn whisper_to_vtt(
+    whisper_output: String,
+    options: Option<VttOptions>,
+) -> Result<String, String> {
+    let doc = SubtitleDocument::from_whisper(&whisper_output).require_cues("whisper output")?;
+    Ok(doc.to_vtt(&options.unwrap_or_default()))
+}
+
+/// Converts SRT content to a WebVTT string, keeping SRT sequence numbers as cue identifiers.
+///
+/// # Arguments
+/// * `srt_content` - The SRT file content as a string.
+/// * `options` - Optional cue identifier and NOTE settings.
+///
+/// # Returns
+/// * `Ok(String)` - The WebVTT formatted string.
+/// * `Err(String)` - If no valid cues are found.
+#[tauri::command]
+pub fn srt_to_vtt(srt_content: String, options: Option<VttOptions>) -> Result<String, String> {
+    let doc = SubtitleDocument::from_srt(&srt_content).require_cues("SRT content")?;
+    Ok(doc.to_vtt(&options.unwrap_or_default()))
+}
+
+/// Converts Whisper output format to an ASS script styled with a saved preset.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle, used to look up style presets.
+/// * `whisper_output` - The output from Whisper as a string.
+/// * `style` - Name of the style preset, defaults to `Default`.
+///
+/// # Returns
+/// * `Ok(String)` - The ASS formatted string.
+/// * `Err(String)` - If no valid lines are found or the preset is invalid.
+#[tauri::command]
+pub fn whisper_to_ass(
+    app: AppHandle,
+    whisper_output: String,
+    style: Option<String>,
+) -> Result<String, String> {
+    let doc = SubtitleDocument::from_whisper(&whisper_output).require_cues("whisper output")?;
+    document_to_ass(app, doc, style, None)
+}
+
+/// Converts SRT content to an ASS script styled with a saved preset.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle, used to look up style presets.
+/// * `srt_content` - The SRT file content as a string.
+/// * `style` - Name of the style preset, defaults to `Default`.
+///
+/// # Returns
+/// * `Ok(String)` - The ASS formatted string.
+/// * `Err(String)` - If no valid cues are found or the preset is invalid.
+#[tauri::command]
+pub fn srt_to_ass(
+    app: AppHandle,
+    srt_content: String,
+    style: Option<String>,
+) -> Result<String, String> {
+    let doc = SubtitleDocument::from_srt(&srt_content).require_cues("SRT content")?;
+    document_to_ass(app, doc, style, None)
+}
+
+/// Tauri command: Parse Whisper console output into a subtitle document.
+#[tauri::command]
+pub fn parse_whisper_output(whisper_output: String) -> Result<SubtitleDocument, String> {
+    SubtitleDocument::from_whisper(&whisper_output).require_cues("whisper output")
+}
+
+/// Tauri command: Parse SRT content into a subtitle document.
+#[tauri::command]
+pub fn parse_srt(srt_content: String) -> Result<SubtitleDocument, String> {
+    SubtitleDocument::from_srt(&srt_content).require_cues("SRT content")
+}
+
+/// Tauri command: Serialize a subtitle document as SRT.
+#[tauri::command]
+pub fn document_to_srt(document: SubtitleDocument) -> Result<String, String> {
+    Ok(document.to_srt())
+}
+
+/// Tauri command: Serialize a subtitle document as WebVTT.
+#[tauri::command]
+pub fn document_to_vtt(
+    document: SubtitleDocument,
+    options: Option<VttOptions>,
+) -> Result<String, String> {
+    Ok(document.to_vtt(&options.unwrap_or_default()))
+}
+
+/// Tauri command: Serialize a subtitle document as ASS using a saved style preset,
+/// optionally with karaoke word highlighting.
+#[tauri::command]
+pub fn document_to_ass(
+    app: AppHandle,
+    document: SubtitleDocument,
+    style: Option<String>,
+    karaoke: Option<bool>,
+) -> Result<String, String> {
+    let style = get_style_preset(&app, style.as_deref().unwrap_or("Default"))?;
+    document.to_ass(&style, karaoke.unwrap_or(false))
+}
+
+/// Tauri command: Return only the text of a subtitle document, one cue per line.
+#[tauri::command]
+pub fn document_to_text(document: SubtitleDocument) -> Result<String, String> {
+    Ok(document.to_plain_text())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(start: u64, end: u64, text: &str) -> Word {
+        Word {
+            start,
+            end,
+            text: text.to_string(),
+            confidence: Some(0.5),
+        }
+    }
+
+    fn replacements(pattern: &str, replacement: &str) -> Replacements {
+        Replacements::new(&[ReplacementRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            regex: false,
+        }])
+        .unwrap()
+    }
+
+    #[test]
+    fn replacement_within_a_word_keeps_its_timing() {
+        let words = [
+            word(0, 400, "Built"),
+            word(400, 900, "with"),
+            word(900, 1500, "tory."),
+        ];
+        let fixed = replacements("tory", "Tauri").apply_to_words(&words);
+        assert_eq!(fixed[..2], words[..2]);
+        assert_eq!(fixed[2], word(900, 1500, "Tauri."));
+    }
+
+    #[test]
+    fn replacement_across_words_merges_them() {
+        let words = [
+            word(0, 300, "Why"),
+            word(300, 700, "sper"),
+            word(700, 1200, "works"),
+        ];
+        let fixed = replacements("why sper", "whisper").apply_to_words(&words);
+        assert_eq!(fixed, [word(0, 700, "Whisper"), word(700, 1200, "works")]);
+    }
+
+    #[test]
+    fn cue_text_and_words_agree_after_replacement() {
+        let mut cue = Cue {
+            id: None,
+            start: 0,
+            end: 1500,
+            text: "Open why sper now".to_string(),
+            speaker: None,
+            words: vec![
+                word(0, 300, "Open"),
+                word(300, 600, "why"),
+                word(600, 900, "sper"),
+                word(900, 1500, "now"),
+            ],
+            confidence: None,
+        };
+        cue.apply_replacements(&replacements("why sper", "whisper"));
+        let words: Vec<&str> = cue.words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(cue.text, "Open whisper now");
+        assert_eq!(words.join(" "), cue.text);
+    }
+
+    #[test]
+    fn srt_blocks_split_on_lines_with_only_whitespace() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,500\r\nFirst\r\n \t\r\n\
+                   2\r\n00:00:03,000 --> 00:00:04,000\r\nSecond\r\nline\r\n";
+        let doc = SubtitleDocument::from_srt(srt);
+        assert_eq!(doc.cues.len(), 2);
+        assert_eq!((doc.cues[0].start, doc.cues[0].end), (1000, 2500));
+        assert_eq!(doc.cues[0].text, "First");
+        assert_eq!(doc.cues[1].id.as_deref(), Some("2"));
+        assert_eq!(doc.cues[1].text, "Second\nline");
+    }
+
+    #[test]
+    fn filter_srt_timestamps_rejects_content_without_cues() {
+        let srt =
+            "1\n00:00:01,000 --> 00:00:02,000\nHello\n\n2\n00:00:02,000 --> 00:00:03,000\nthere";
+        assert_eq!(
+            filter_srt_timestamps(srt.to_string()).unwrap(),
+            "Hello\nthere"
+        );
+        assert!(filter_srt_timestamps("not subtitles".to_string()).is_err());
+    }
+
+    #[test]
+    fn vtt_has_header_notes_and_cue_ids() {
+        let mut first = Cue::new(0, 1500, "Hello");
+        first.id = Some("intro".to_string());
+        let doc = SubtitleDocument {
+            cues: vec![first, Cue::new(1500, 3000, "World")],
+        };
+        let vtt = doc.to_vtt(&VttOptions {
+            cue_ids: true,
+            notes: vec!["Made with --> captions\n\nline two".to_string()],
+            word_timestamps: false,
+        });
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\nNOTE\nMade with -> captions\nline two\n\n\
+             intro\n00:00:00.000 --> 00:00:01.500\nHello\n\n\
+             2\n00:00:01.500 --> 00:00:03.000\nWorld\n"
+        );
+    }
+
+    #[test]
+    fn vtt_escapes_cue_text_and_speakers() {
+        let mut cue = Cue::new(0, 1000, "a <b & c> d");
+        cue.speaker = Some("Dr. <X>".to_string());
+        let doc = SubtitleDocument { cues: vec![cue] };
+        let vtt = doc.to_vtt(&VttOptions::default());
+        assert!(
+            vtt.ends_with("\n<v Dr. &lt;X&gt;>a &lt;b &amp; c&gt; d\n"),
+            "{}",
+            vtt
+        );
+    }
+
+    #[test]
+    fn vtt_escapes_karaoke_words() {
+        let mut cue = Cue::new(0, 1000, "a <b");
+        cue.words = vec![word(0, 400, "a"), word(400, 1000, "<b")];
+        let doc = SubtitleDocument { cues: vec![cue] };
+        let vtt = doc.to_vtt(&VttOptions {
+            word_timestamps: true,
+            ..VttOptions::default()
+        });
+        assert!(vtt.ends_with("\na <00:00:00.400>&lt;b\n"), "{}", vtt);
+    }
+
+    #[test]
+    fn ass_has_style_colours_speakers_and_escaped_text() {
+        let style = AssStyle {
+            primary_color: "#FF8000".to_string(),
+            back_color: "#10203040".to_string(),
+            ..AssStyle::default()
+        };
+        let mut cue = Cue::new(1250, 3500, "Use {\\b1} here\nand there");
+        cue.speaker = Some("Ann, Host".to_string());
+        let doc = SubtitleDocument { cues: vec![cue] };
+        let ass = doc.to_ass(&style, false).unwrap();
+        // `&HAABBGGRR` with ASS alpha inverted: 0x40 opacity becomes 0xBF transparency
+        assert!(ass
+            .contains("Style: Default,Arial,64,&H000080FF,&H0000D7FF,&H00000000,&HBF302010,0,0,"));
+        assert!(ass.ends_with(
+            "Dialogue: 0,0:00:01.25,0:00:03.50,Default,Ann  Host,0,0,0,,\
+             Use \\{\\b1\\} here\\Nand there\n"
+        ));
+    }
+
+    #[test]
+    fn ass_karaoke_times_words_in_centiseconds() {
+        let mut cue = Cue::new(1000, 2500, "Hello big world");
+        cue.words = vec![
+            word(1000, 1350, "Hello"),
+            word(1450, 1800, "big"),
+            word(1800, 2600, "world"),
+        ];
+        let doc = SubtitleDocument { cues: vec![cue] };
+        let ass = doc.to_ass(&AssStyle::default(), true).unwrap();
+        assert!(
+            ass.ends_with(",,{\\k35}Hello {\\k10}{\\k35}big {\\k70}world\n"),
+            "{}",
+            ass
+        );
+    }
+
+    #[test]
+    fn ass_rejects_invalid_colours() {
+        let style = AssStyle {
+            outline_color: "black".to_string(),
+            ..AssStyle::default()
+        };
+        assert!(SubtitleDocument::default().to_ass(&style, false).is_err());
+    }
 }
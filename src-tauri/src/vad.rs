@@ -0,0 +1,349 @@
+//! Energy-based voice activity detection on decoded PCM, used to cut long silences out of
+//! the audio before inference so whisper has nothing to hallucinate on.
+
+use hound::{SampleFormat, WavReader, WavWriter};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Length of the frames loudness is measured over, in milliseconds.
+const FRAME_MS: u64 = 30;
+
+/// Silence inserted between speech regions in the compacted audio, so whisper still hears
+/// a pause there, in milliseconds.
+const GAP_MS: u64 = 300;
+
+/// Speech shorter than this is treated as a click or bump, in milliseconds.
+const MIN_SPEECH_MS: u64 = 90;
+
+/// Settings for skipping non-speech audio.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VadOptions {
+    pub enabled: bool,
+    /// Frames louder than this (dBFS) count as speech. Defaults to 12 dB above the
+    /// recording's noise floor, kept between -60 and -35 dBFS.
+    pub threshold_db: Option<f32>,
+    /// Pauses shorter than this are kept, in milliseconds.
+    pub min_silence_ms: u64,
+    /// Audio kept before and after each speech region, in milliseconds.
+    pub padding_ms: u64,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        VadOptions {
+            enabled: false,
+            threshold_db: None,
+            min_silence_ms: 1000,
+            padding_ms: 250,
+        }
+    }
+}
+
+/// A stretch of speech copied into the compacted audio.
+#[derive(Debug, Clone)]
+struct SpeechRegion {
+    compact_start: u64,
+    original_start: u64,
+    len: u64,
+}
+
+/// Maps times in the compacted audio back to the audio it was cut from.
+#[derive(Debug, Clone, Default)]
+pub struct SpeechMap {
+    regions: Vec<SpeechRegion>,
+}
+
+impl SpeechMap {
+    /// Returns true when no speech was found.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Converts a time in the compacted audio to the original timeline. Times inside an
+    /// inserted gap map to the end of the speech before it.
+    pub fn to_original(&self, ms: u64) -> u64 {
+        let index = self.regions.partition_point(|r| r.compact_start <= ms);
+        match index.checked_sub(1).map(|i| &self.regions[i]) {
+            Some(region) => region.original_start + (ms - region.compact_start).min(region.len),
+            None => self.regions.first().map_or(ms, |r| r.original_start),
+        }
+    }
+}
+
+/// Reads a 16-bit mono WAV and returns the loudness of each frame in dBFS.
+fn frame_levels(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader =
+        WavReader::open(path).map_err(|e| format!("[vad] Failed to read decoded audio: {}", e))?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.bits_per_sample != 16 || spec.sample_format != SampleFormat::Int {
+        return Err("[vad] Expected 16-bit mono PCM audio".to_string());
+    }
+    let frame_len = (spec.sample_rate as u64 * FRAME_MS / 1000).max(1) as usize;
+    let mut levels = Vec::new();
+    let mut sum = 0.0f64;
+    let mut count = 0;
+    for sample in reader.samples::<i16>() {
+        let sample = sample.map_err(|e| format!("[vad] Failed to read decoded audio: {}", e))?;
+        let value = sample as f64 / i16::MAX as f64;
+        sum += value * value;
+        count += 1;
+        if count == frame_len {
+            levels.push(rms_db(sum, count));
+            sum = 0.0;
+            count = 0;
+        }
+    }
+    if count > 0 {
+        levels.push(rms_db(sum, count));
+    }
+    Ok((levels, spec.sample_rate))
+}
+
+fn rms_db(sum_of_squares: f64, count: usize) -> f32 {
+    let rms = (sum_of_squares / count as f64).sqrt();
+    (20.0 * rms.max(1e-5).log10()) as f32
+}
+
+/// Finds speech regions, as start and end in milliseconds, from per-frame loudness.
+fn speech_regions(levels: &[f32], options: &VadOptions) -> Vec<(u64, u64)> {
+    if levels.is_empty() {
+        return Vec::new();
+    }
+    // Clamped so recordings without pauses still pass as speech and near-digital silence
+    // does not turn faint noise into speech
+    let threshold = options.threshold_db.unwrap_or_else(|| {
+        let mut sorted = levels.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        (sorted[sorted.len() / 10] + 12.0).clamp(-60.0, -35.0)
+    });
+    let total_ms = levels.len() as u64 * FRAME_MS;
+
+    let mut regions: Vec<(u64, u64)> = Vec::new();
+    let mut start = None;
+    for (i, level) in levels.iter().enumerate() {
+        let ms = i as u64 * FRAME_MS;
+        match (start, *level >= threshold) {
+            (None, true) => start = Some(ms),
+            (Some(s), false) => {
+                regions.push((s, ms));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        regions.push((s, total_ms));
+    }
+
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in regions {
+        if end - start < MIN_SPEECH_MS {
+            continue;
+        }
+        let start = start.saturating_sub(options.padding_ms);
+        let end = (end + options.padding_ms).min(total_ms);
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + options.min_silence_ms => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Writes the speech in `input` (16-bit mono WAV) to `output`, dropping the silence between
+/// speech regions.
+///
+/// # Returns
+/// * `Ok(SpeechMap)` - Mapping from the compacted audio back to `input`; empty (and no file
+///   written) when there is no speech
+/// * `Err(String)` - Error message if reading or writing fails
+pub fn compact_speech(
+    input: &Path,
+    output: &Path,
+    options: &VadOptions,
+) -> Result<SpeechMap, String> {
+    let (levels, sample_rate) = frame_levels(input)?;
+    let regions = speech_regions(&levels, options);
+    if regions.is_empty() {
+        return Ok(SpeechMap::default());
+    }
+    let to_sample = |ms: u64| ms * sample_rate as u64 / 1000;
+
+    let mut reader =
+        WavReader::open(input).map_err(|e| format!("[vad] Failed to read decoded audio: {}", e))?;
+    let mut writer = WavWriter::create(output, reader.spec())
+        .map_err(|e| format!("[vad] Failed to write speech audio: {}", e))?;
+    let write_error = |e: hound::Error| format!("[vad] Failed to write speech audio: {}", e);
+
+    let mut map = SpeechMap::default();
+    let mut compact_ms = 0;
+    let mut samples = reader.samples::<i16>().enumerate();
+    for (i, &(start, end)) in regions.iter().enumerate() {
+        if i > 0 {
+            for _ in 0..to_sample(GAP_MS) {
+                writer.write_sample(0i16).map_err(write_error)?;
+            }
+            compact_ms += GAP_MS;
+        }
+        let (first, last) = (to_sample(start), to_sample(end));
+        for (index, sample) in samples.by_ref() {
+            let index = index as u64;
+            if index < first {
+                continue;
+            }
+            let sample =
+                sample.map_err(|e| format!("[vad] Failed to read decoded audio: {}", e))?;
+            writer.write_sample(sample).map_err(write_error)?;
+            if index + 1 >= last {
+                break;
+            }
+        }
+        map.regions.push(SpeechRegion {
+            compact_start: compact_ms,
+            original_start: start,
+            len: end - start,
+        });
+        compact_ms += end - start;
+    }
+    writer.finalize().map_err(write_error)?;
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds per-frame levels from runs of `(dBFS, frame count)`.
+    fn levels(runs: &[(f32, usize)]) -> Vec<f32> {
+        runs.iter()
+            .flat_map(|&(level, frames)| vec![level; frames])
+            .collect()
+    }
+
+    fn options(min_silence_ms: u64, padding_ms: u64) -> VadOptions {
+        VadOptions {
+            enabled: true,
+            threshold_db: Some(-40.0),
+            min_silence_ms,
+            padding_ms,
+        }
+    }
+
+    #[test]
+    fn finds_speech_between_silences() {
+        let levels = levels(&[(-80.0, 20), (-20.0, 10), (-80.0, 20)]);
+        assert_eq!(speech_regions(&levels, &options(1000, 0)), [(600, 900)]);
+        assert_eq!(speech_regions(&levels, &options(1000, 250)), [(350, 1150)]);
+    }
+
+    #[test]
+    fn derives_the_threshold_from_the_noise_floor() {
+        let levels = levels(&[(-70.0, 20), (-30.0, 10), (-70.0, 20)]);
+        let options = VadOptions {
+            threshold_db: None,
+            ..options(1000, 0)
+        };
+        assert_eq!(speech_regions(&levels, &options), [(600, 900)]);
+    }
+
+    #[test]
+    fn keeps_pauses_shorter_than_min_silence() {
+        // Two words 300 ms apart
+        let levels = levels(&[
+            (-80.0, 10),
+            (-20.0, 10),
+            (-80.0, 10),
+            (-20.0, 10),
+            (-80.0, 10),
+        ]);
+        assert_eq!(speech_regions(&levels, &options(1000, 0)), [(300, 1200)]);
+        assert_eq!(
+            speech_regions(&levels, &options(200, 0)),
+            [(300, 600), (900, 1200)]
+        );
+    }
+
+    #[test]
+    fn padding_stops_at_the_file_edges() {
+        let levels = levels(&[(-20.0, 10), (-80.0, 50), (-20.0, 10)]);
+        assert_eq!(
+            speech_regions(&levels, &options(500, 250)),
+            [(0, 550), (1550, 2100)]
+        );
+    }
+
+    #[test]
+    fn drops_clicks_and_silent_audio() {
+        let clicks = levels(&[(-80.0, 20), (-20.0, 2), (-80.0, 20)]);
+        assert!(speech_regions(&clicks, &options(1000, 250)).is_empty());
+        assert!(speech_regions(&[], &options(1000, 250)).is_empty());
+    }
+
+    fn two_region_map() -> SpeechMap {
+        SpeechMap {
+            regions: vec![
+                SpeechRegion {
+                    compact_start: 0,
+                    original_start: 1000,
+                    len: 2000,
+                },
+                SpeechRegion {
+                    compact_start: 2000 + GAP_MS,
+                    original_start: 5000,
+                    len: 1000,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn maps_times_inside_regions_back() {
+        let map = two_region_map();
+        assert_eq!(map.to_original(0), 1000);
+        assert_eq!(map.to_original(500), 1500);
+        assert_eq!(map.to_original(2000 + GAP_MS + 200), 5200);
+        // Past the end of the compacted audio
+        assert_eq!(map.to_original(10_000), 6000);
+    }
+
+    #[test]
+    fn maps_times_inside_gaps_to_the_speech_before() {
+        let map = two_region_map();
+        assert_eq!(map.to_original(2000), 3000);
+        assert_eq!(map.to_original(2000 + GAP_MS / 2), 3000);
+        assert_eq!(map.to_original(2000 + GAP_MS), 5000);
+    }
+
+    #[test]
+    fn compacts_a_wav_and_maps_it_back() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("captions-test-{}.wav", uuid::Uuid::new_v4()));
+        let output = dir.join(format!("captions-test-{}.wav", uuid::Uuid::new_v4()));
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&input, spec).unwrap();
+        // 3 s of silence, 1.5 s of tone, 3 s of silence, 1.5 s of tone
+        for (seconds, loud) in [(3.0, false), (1.5, true), (3.0, false), (1.5, true)] {
+            for i in 0..(16000.0 * seconds) as usize {
+                let sample = if loud && i % 32 < 16 { 8000 } else { 0 };
+                writer.write_sample(sample as i16).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+
+        let map = compact_speech(&input, &output, &options(1000, 0)).unwrap();
+        let compacted = WavReader::open(&output).unwrap().duration() as u64;
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+
+        assert_eq!(map.regions.len(), 2);
+        assert_eq!(compacted, (1500 + GAP_MS + 1500) * 16);
+        assert_eq!(map.to_original(100), 3100);
+        assert_eq!(map.to_original(1500 + GAP_MS + 100), 7600);
+    }
+}
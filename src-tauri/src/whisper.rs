@@ -1,31 +1,475 @@
 //! Whisper CLI integration and audio transcription utilities.
 
-use crate::ffmpeg::spawn_ffmpeg_to_wav;
+use crate::context::AppContext;
+use crate::ffmpeg::{
+    convert_to_wav_file, detect_silences, spawn_ffmpeg_to_wav, AudioFilters, WavConversion,
+};
+use crate::glossaries::get_glossary;
+use crate::jobs::{finish_job, register_job, track_process, track_temp_file};
 use crate::models::get_models_dir;
-use std::io::Read;
+use crate::probe::probe;
+use crate::subtitles::{parse_whisper_line, Cue, Replacements, SubtitleDocument, Word};
+use crate::vad::{compact_speech, VadOptions};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use tauri::path::BaseDirectory;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::AppHandle;
-use tauri::Manager;
 
 /// Resolves the path to the whisper binary bundled with the app.
-fn resolve_whisper_bin(app: &AppHandle) -> Result<std::path::PathBuf, String> {
-    app.path()
-        .resolve("gen/whisper-bin/whisper", BaseDirectory::Resource)
+fn resolve_whisper_bin(app: &impl AppContext) -> Result<std::path::PathBuf, String> {
+    app.resource_path("gen/whisper-bin/whisper")
         .map_err(|e| format!("[whisper] Failed to resolve whisper binary: {}", e))
 }
 
-/// Builds the argument list for the whisper CLI.
-fn build_whisper_args(
-    model_path: &str,
-    language: &str,
-    input: &str,
-    is_stdin: bool,
-) -> Vec<String> {
-    let mut args = vec!["-m".into(), model_path.into(), "-l".into(), language.into()];
-    args.push("-f".into());
-    args.push(if is_stdin { "-".into() } else { input.into() });
-    args
+/// A single decoded token with its timing and probability.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    pub id: i64,
+    pub text: String,
+    /// Start time in milliseconds.
+    pub start: u64,
+    /// End time in milliseconds.
+    pub end: u64,
+    pub probability: f32,
+    /// DTW-aligned token onset in milliseconds, when DTW alignment was enabled.
+    pub dtw: Option<u64>,
+}
+
+/// A transcribed segment with its tokens.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    /// Start time in milliseconds.
+    pub start: u64,
+    /// End time in milliseconds.
+    pub end: u64,
+    pub text: String,
+    pub tokens: Vec<Token>,
+    /// Word timings, only filled in word-timestamp mode.
+    pub words: Vec<Word>,
+}
+
+/// Structured result of a transcription run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcription {
+    pub language: Option<String>,
+    /// Confidence of the detected language, only set when the language was `auto`.
+    pub language_probability: Option<f32>,
+    pub segments: Vec<Segment>,
+    /// English translation of the same audio, only set for the `translate` task. Its segment
+    /// boundaries are whisper's own and need not match `segments`.
+    pub translation: Option<Vec<Segment>>,
+}
+
+/// What whisper produces from the audio.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WhisperTask {
+    /// Text in the spoken language.
+    #[default]
+    Transcribe,
+    /// Text in the spoken language plus an English translation (`-tr`).
+    Translate,
+}
+
+/// Which text of a transcription becomes the subtitle text.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptText {
+    #[default]
+    Original,
+    Translation,
+    /// The original with the translation as a second line.
+    Bilingual,
+}
+
+impl Transcription {
+    /// Joins transcriptions of consecutive chunks that were already shifted onto the full
+    /// timeline. `chunks` holds each chunk's end time with its transcription; segments are
+    /// clamped to their chunk so neighbouring chunks never overlap.
+    pub fn stitch(chunks: Vec<(u64, Transcription)>) -> Transcription {
+        let mut stitched = Transcription {
+            language: None,
+            language_probability: None,
+            segments: Vec::new(),
+            translation: None,
+        };
+        for (chunk_end, chunk) in chunks {
+            if stitched.language.is_none() {
+                stitched.language = chunk.language;
+                stitched.language_probability = chunk.language_probability;
+            }
+            for mut segment in chunk.segments {
+                // whisper sometimes places the last segment past the end of its audio
+                if segment.start >= chunk_end {
+                    continue;
+                }
+                segment.end = segment.end.min(chunk_end);
+                for word in &mut segment.words {
+                    word.start = word.start.min(chunk_end);
+                    word.end = word.end.min(chunk_end);
+                }
+                stitched.segments.push(segment);
+            }
+        }
+        stitched
+    }
+
+    /// Applies replacement rules to the text and words of the segments and the translation.
+    pub fn apply_replacements(&mut self, replacements: &Replacements) {
+        let translation = self.translation.iter_mut().flatten();
+        for segment in self.segments.iter_mut().chain(translation) {
+            segment.text = replacements.apply(&segment.text);
+            segment.words = replacements.apply_to_words(&segment.words);
+        }
+    }
+
+    /// Moves every segment, token and word onto another timeline, e.g. to map a
+    /// transcription of a clipped range back onto the full file's timeline.
+    pub fn map_times(&mut self, map: impl Fn(u64) -> u64) {
+        for segment in &mut self.segments {
+            segment.start = map(segment.start);
+            segment.end = map(segment.end);
+            for token in &mut segment.tokens {
+                token.start = map(token.start);
+                token.end = map(token.end);
+                token.dtw = token.dtw.map(&map);
+            }
+            for word in &mut segment.words {
+                word.start = map(word.start);
+                word.end = map(word.end);
+            }
+        }
+    }
+}
+
+impl Segment {
+    /// Tokens that carry text, skipping whisper's special `[_BEG_]`/`[_TT_n]` style markers.
+    pub fn text_tokens(&self) -> impl Iterator<Item = &Token> {
+        self.tokens.iter().filter(|t| !t.text.starts_with("[_"))
+    }
+
+    /// Merges sub-word tokens into words. A token starting with whitespace begins a new word;
+    /// anything else (word pieces, punctuation) is appended to the current one.
+    fn build_words(&self) -> Vec<Word> {
+        let mut words: Vec<(Word, Vec<f32>)> = Vec::new();
+        for token in self.text_tokens() {
+            let starts_word = token.text.starts_with(char::is_whitespace);
+            match words.last_mut() {
+                Some((word, probabilities)) if !starts_word => {
+                    word.text.push_str(token.text.trim());
+                    word.end = word.end.max(token.end);
+                    probabilities.push(token.probability);
+                }
+                _ => {
+                    let text = token.text.trim();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let start = token.dtw.unwrap_or(token.start);
+                    words.push((
+                        Word {
+                            start,
+                            end: token.end.max(start),
+                            text: text.to_string(),
+                            confidence: None,
+                        },
+                        vec![token.probability],
+                    ));
+                }
+            }
+        }
+        words
+            .into_iter()
+            .map(|(mut word, probabilities)| {
+                word.confidence =
+                    Some(probabilities.iter().sum::<f32>() / probabilities.len() as f32);
+                word
+            })
+            .collect()
+    }
+}
+
+impl Transcription {
+    /// Builds a subtitle document from the original text, the translation or both.
+    ///
+    /// # Returns
+    /// * `Ok(SubtitleDocument)` - The document
+    /// * `Err(String)` - The translation was requested but the transcription has none
+    pub fn to_document(&self, text: TranscriptText) -> Result<SubtitleDocument, String> {
+        let original = SubtitleDocument::from(self);
+        if text == TranscriptText::Original {
+            return Ok(original);
+        }
+        let translation = self
+            .translation
+            .as_deref()
+            .map(segments_to_document)
+            .ok_or("The transcription has no translation, run it with the translate task")?;
+        Ok(match text {
+            TranscriptText::Translation => translation,
+            _ => original.with_translation(&translation),
+        })
+    }
+}
+
+/// Converts segments to cues, averaging token probabilities into each cue's confidence.
+fn segments_to_document(segments: &[Segment]) -> SubtitleDocument {
+    let cues = segments
+        .iter()
+        .map(|segment| {
+            let mut cue = Cue::new(segment.start, segment.end, segment.text.trim());
+            cue.words = segment.words.clone();
+            let probabilities: Vec<f32> = segment.text_tokens().map(|t| t.probability).collect();
+            if !probabilities.is_empty() {
+                cue.confidence =
+                    Some(probabilities.iter().sum::<f32>() / probabilities.len() as f32);
+            }
+            cue
+        })
+        .collect();
+    SubtitleDocument { cues }
+}
+
+/// Parses JSON holding either a subtitle document or a transcription, keeping word timings.
+pub fn document_from_json(contents: &str) -> Result<SubtitleDocument, serde_json::Error> {
+    serde_json::from_str::<SubtitleDocument>(contents).or_else(|_| {
+        serde_json::from_str::<Transcription>(contents).map(|t| SubtitleDocument::from(&t))
+    })
+}
+
+impl From<&Transcription> for SubtitleDocument {
+    fn from(transcription: &Transcription) -> Self {
+        segments_to_document(&transcription.segments)
+    }
+}
+
+/// Raw whisper-cli `-ojf` output, only the fields we use.
+#[derive(Deserialize)]
+struct WhisperJson {
+    #[serde(default)]
+    result: Option<WhisperJsonResult>,
+    #[serde(default)]
+    transcription: Vec<WhisperJsonSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonResult {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonOffsets {
+    from: u64,
+    to: u64,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonSegment {
+    offsets: WhisperJsonOffsets,
+    text: String,
+    #[serde(default)]
+    tokens: Vec<WhisperJsonToken>,
+}
+
+#[derive(Deserialize)]
+struct WhisperJsonToken {
+    #[serde(default)]
+    id: i64,
+    text: String,
+    offsets: WhisperJsonOffsets,
+    #[serde(default)]
+    p: f32,
+    /// DTW onset in centiseconds, -1 when not computed.
+    #[serde(default = "no_dtw")]
+    t_dtw: i64,
+}
+
+fn no_dtw() -> i64 {
+    -1
+}
+
+impl From<WhisperJson> for Transcription {
+    fn from(json: WhisperJson) -> Self {
+        Transcription {
+            language: json.result.and_then(|r| r.language),
+            language_probability: None,
+            translation: None,
+            segments: json
+                .transcription
+                .into_iter()
+                .map(|s| Segment {
+                    start: s.offsets.from,
+                    end: s.offsets.to,
+                    text: s.text,
+                    tokens: s
+                        .tokens
+                        .into_iter()
+                        .map(|t| Token {
+                            id: t.id,
+                            text: t.text,
+                            start: t.offsets.from,
+                            end: t.offsets.to,
+                            probability: t.p,
+                            dtw: (t.t_dtw >= 0).then(|| t.t_dtw as u64 * 10),
+                        })
+                        .collect(),
+                    words: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Returns a unique output base path in the temp dir; whisper-cli appends `.json` to it.
+fn temp_output_base() -> PathBuf {
+    std::env::temp_dir().join(format!("captions-{}", uuid::Uuid::new_v4()))
+}
+
+/// Reads and deletes the JSON file whisper-cli wrote for `output_base`.
+fn read_whisper_json(output_base: &Path) -> Result<Transcription, String> {
+    let json_path = output_base.with_extension("json");
+    let contents = std::fs::read_to_string(&json_path)
+        .map_err(|e| format!("[whisper] Failed to read whisper JSON output: {}", e));
+    let _ = std::fs::remove_file(&json_path);
+    let json: WhisperJson = serde_json::from_str(&contents?)
+        .map_err(|e| format!("[whisper] Failed to parse whisper JSON output: {}", e))?;
+    Ok(json.into())
+}
+
+/// Maps a model file name like `ggml-large-v3-turbo.bin` to whisper.cpp's DTW alignment
+/// preset (`large.v3.turbo`), if one exists for it.
+fn dtw_preset_for_model(model: &str) -> Option<String> {
+    const PRESETS: [&str; 12] = [
+        "tiny",
+        "tiny.en",
+        "base",
+        "base.en",
+        "small",
+        "small.en",
+        "medium",
+        "medium.en",
+        "large.v1",
+        "large.v2",
+        "large.v3",
+        "large.v3.turbo",
+    ];
+    let preset = model
+        .strip_prefix("ggml-")?
+        .strip_suffix(".bin")?
+        .replace('-', ".");
+    PRESETS.contains(&preset.as_str()).then_some(preset)
+}
+
+/// Decoding settings passed to whisper-cli. Unset values keep whisper-cli's defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WhisperOptions {
+    /// Beams searched per segment (`-bs`), 1 decodes greedily.
+    pub beam_size: Option<u32>,
+    /// Candidates sampled when decoding with a temperature above 0 (`-bo`).
+    pub best_of: Option<u32>,
+    /// Initial sampling temperature, between 0 and 1 (`-tp`).
+    pub temperature: Option<f32>,
+    /// Temperature added on each fallback after a failed decode, between 0 and 1 (`-tpi`).
+    pub temperature_increment: Option<f32>,
+    /// Never retry a failed decode at a higher temperature (`-nf`).
+    pub no_fallback: bool,
+    /// Decodes with a higher entropy count as failed (`-et`).
+    pub entropy_threshold: Option<f32>,
+    /// Decodes with a lower average log probability count as failed (`-lpt`).
+    pub logprob_threshold: Option<f32>,
+    /// Maximum segment length in characters (`-ml`), 0 for no limit.
+    pub max_segment_length: Option<u32>,
+    /// Split segments at word boundaries rather than tokens when limiting their length
+    /// (`-sow`).
+    pub split_on_word: bool,
+    /// CPU threads per whisper-cli process (`-t`). Defaults to the available cores divided
+    /// between parallel workers.
+    pub threads: Option<u32>,
+    /// Text that primes the decoder, e.g. names and spellings to expect (`--prompt`).
+    pub prompt: Option<String>,
+    /// Suppress non-speech tokens like music notes and sound descriptions (`-sns`).
+    pub suppress_non_speech: bool,
+}
+
+impl WhisperOptions {
+    /// Checks that every value is within the range whisper-cli accepts.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.beam_size.is_some_and(|n| !(1..=16).contains(&n)) {
+            return Err("Beam size must be between 1 and 16".to_string());
+        }
+        if self.best_of.is_some_and(|n| !(1..=16).contains(&n)) {
+            return Err("Best-of must be between 1 and 16".to_string());
+        }
+        for (name, value) in [
+            ("Temperature", self.temperature),
+            ("Temperature increment", self.temperature_increment),
+        ] {
+            if value.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                return Err(format!("{} must be between 0 and 1", name));
+            }
+        }
+        if self
+            .entropy_threshold
+            .is_some_and(|v| !v.is_finite() || v <= 0.0)
+        {
+            return Err("Entropy threshold must be above 0".to_string());
+        }
+        if self
+            .logprob_threshold
+            .is_some_and(|v| !v.is_finite() || v > 0.0)
+        {
+            return Err("Log probability threshold must be 0 or below".to_string());
+        }
+        if self.threads == Some(0) {
+            return Err("Thread count must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Maps the options to whisper-cli flags.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
+        let mut push = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.extend([flag.to_string(), value]);
+            }
+        };
+        push("-bs", self.beam_size.map(|n| n.to_string()));
+        push("-bo", self.best_of.map(|n| n.to_string()));
+        push("-tp", self.temperature.map(|v| v.to_string()));
+        push("-tpi", self.temperature_increment.map(|v| v.to_string()));
+        push("-et", self.entropy_threshold.map(|v| v.to_string()));
+        push("-lpt", self.logprob_threshold.map(|v| v.to_string()));
+        push("-ml", self.max_segment_length.map(|n| n.to_string()));
+        push("-t", self.threads.map(|n| n.to_string()));
+        push(
+            "--prompt",
+            self.prompt
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+        );
+        for (flag, enabled) in [
+            ("-nf", self.no_fallback),
+            ("-sow", self.split_on_word),
+            ("-sns", self.suppress_non_speech),
+        ] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        args
+    }
 }
 
 /// Runs the whisper CLI with the given arguments and returns the output.
@@ -53,11 +497,322 @@ fn run_whisper_cli_internal(
     }
 }
 
-/// Tauri command: Run the whisper CLI with arbitrary arguments.
+/// Payload of the `transcription-progress` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptionProgress {
+    job_id: String,
+    percent: u32,
+}
+
+/// Payload of the `transcription-segment` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptionSegment {
+    job_id: String,
+    cue: Cue,
+    /// Whether the cue belongs to the English translation pass.
+    translation: bool,
+}
+
+/// Matches whisper-cli's `-pp` progress lines on stderr, e.g. `... progress =  45%`.
+static PROGRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"progress\s*=\s*(\d+)%").expect("Failed to compile whisper progress regex")
+});
+
+/// Matches the language whisper-cli reports on stderr when run with `-l auto`, e.g.
+/// `whisper_full_with_state: auto-detected language: de (p = 0.974512)`.
+static DETECTED_LANGUAGE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"auto-detected language: (\w+) \(p = ([\d.]+)\)")
+        .expect("Failed to compile whisper language regex")
+});
+
+/// Language value that makes whisper-cli detect the spoken language itself.
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Length of audio the language detection pass listens to, in milliseconds.
+const DETECTION_MS: u64 = 30_000;
+
+/// Extracts the detected language and its probability from a whisper-cli log.
+fn parse_detected_language(log: &str) -> Option<(String, f32)> {
+    let caps = DETECTED_LANGUAGE_REGEX.captures(log)?;
+    Some((caps[1].to_string(), caps[2].parse().ok()?))
+}
+
+fn emit_progress(app: &impl AppContext, job_id: &str, percent: u32) {
+    app.emit_event(
+        "transcription-progress",
+        TranscriptionProgress {
+            job_id: job_id.to_string(),
+            percent: percent.min(100),
+        },
+    );
+}
+
+/// Runs whisper-cli, passing segments streamed on stdout to `on_cue` and `-pp` progress from
+/// stderr to `progress`. The process is registered with `job_id` so it can be cancelled.
+/// Returns the rest of whisper's stderr log.
+fn run_whisper_streaming(
+    job_id: &str,
+    bin_path: &Path,
+    args: &[String],
+    stdin: Option<Stdio>,
+    on_cue: impl Fn(Cue),
+    progress: impl Fn(u32) + Send + 'static,
+) -> Result<String, String> {
+    let mut cmd = Command::new(bin_path);
+    cmd.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+    if let Some(stdin) = stdin {
+        cmd.stdin(stdin);
+    }
+    let mut whisper = cmd
+        .spawn()
+        .map_err(|e| format!("[whisper] Failed to start whisper CLI: {}", e))?;
+    let stdout = whisper.stdout.take();
+    let stderr = whisper.stderr.take();
+    let whisper = track_process(job_id, whisper);
+    // Drain stderr on its own thread so neither pipe can fill up and block whisper
+    let stderr_thread = std::thread::spawn(move || {
+        let mut log = String::new();
+        if let Some(stderr) = stderr {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                match PROGRESS_REGEX.captures(&line) {
+                    Some(caps) => progress(caps[1].parse().unwrap_or(0)),
+                    None => {
+                        log.push_str(&line);
+                        log.push('\n');
+                    }
+                }
+            }
+        }
+        log
+    });
+    if let Some(stdout) = stdout {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(cue) = parse_whisper_line(&line) {
+                on_cue(cue);
+            }
+        }
+    }
+    let status = whisper
+        .lock()
+        .unwrap()
+        .wait()
+        .map_err(|e| format!("[whisper] Failed to wait for whisper: {}", e))?;
+    let log = stderr_thread.join().unwrap_or_default();
+    if status.success() {
+        Ok(log)
+    } else {
+        Err(format!("whisper CLI failed: {}", log.trim()))
+    }
+}
+
+/// Request accepted by `run_whisper_cli`. Only these fields are mapped to whisper-cli flags;
+/// requests with any other field are refused.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WhisperCliRequest {
+    /// Audio file whisper-cli reads directly (WAV, MP3, FLAC or OGG).
+    pub input_path: String,
+    /// File name of an installed model, e.g. `ggml-base.en.bin`.
+    pub model: String,
+    /// Language code, or `auto` to detect it.
+    pub language: String,
+    #[serde(default)]
+    pub task: WhisperTask,
+    #[serde(default)]
+    pub options: WhisperOptions,
+}
+
+/// Error returned by `run_whisper_cli`. Every variant but `Failed` is a rejection: the
+/// request was refused before whisper-cli was started.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum WhisperCliError {
+    /// The model is not the file name of an installed model.
+    UnknownModel(String),
+    /// The language is neither `auto` nor a language code.
+    InvalidLanguage(String),
+    /// The input does not exist or lies outside the permitted directories.
+    PathNotPermitted(String),
+    /// A decoding option is out of range.
+    InvalidOption(String),
+    /// whisper-cli was started and failed.
+    Failed(String),
+}
+
+impl std::fmt::Display for WhisperCliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WhisperCliError::UnknownModel(model) => write!(f, "Model {} is not installed", model),
+            WhisperCliError::InvalidLanguage(language) => {
+                write!(f, "Invalid language code: {}", language)
+            }
+            WhisperCliError::PathNotPermitted(path) => {
+                write!(f, "Input {} is not in a permitted directory", path)
+            }
+            WhisperCliError::InvalidOption(message) | WhisperCliError::Failed(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+/// Resolves an input path, following symlinks, and checks that it is a file the user picked or
+/// one inside the app data or temp directory but not inside a hidden directory there.
+fn permitted_input(app: &impl AppContext, path: &str) -> Result<PathBuf, WhisperCliError> {
+    let rejected = || WhisperCliError::PathNotPermitted(path.to_string());
+    let resolved = std::fs::canonicalize(path).map_err(|_| rejected())?;
+    if !resolved.is_file() {
+        return Err(rejected());
+    }
+    if app.user_selected(&resolved) {
+        return Ok(resolved);
+    }
+    let roots = [app.app_data_dir(), std::env::temp_dir()];
+    let permitted = roots
+        .into_iter()
+        .filter_map(|root| std::fs::canonicalize(root).ok())
+        .any(|root| {
+            resolved.strip_prefix(&root).is_ok_and(|relative| {
+                !relative
+                    .components()
+                    .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+            })
+        });
+    if permitted {
+        Ok(resolved)
+    } else {
+        Err(rejected())
+    }
+}
+
+/// Validates a `run_whisper_cli` request and builds the whisper-cli arguments for it.
+fn whisper_cli_args(
+    app: &impl AppContext,
+    request: &WhisperCliRequest,
+) -> Result<Vec<String>, WhisperCliError> {
+    let model_path = get_models_dir(app).join(&request.model);
+    let is_file_name = Path::new(&request.model).file_name() == Some(request.model.as_ref());
+    if !is_file_name || !request.model.ends_with(".bin") || !model_path.is_file() {
+        return Err(WhisperCliError::UnknownModel(request.model.clone()));
+    }
+    let language = &request.language;
+    let is_code =
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase());
+    if language != AUTO_LANGUAGE && !is_code {
+        return Err(WhisperCliError::InvalidLanguage(language.clone()));
+    }
+    request
+        .options
+        .validate()
+        .map_err(WhisperCliError::InvalidOption)?;
+    let input = permitted_input(app, &request.input_path)?;
+
+    let mut args = vec![
+        "-m".into(),
+        model_path.to_string_lossy().into_owned(),
+        "-l".into(),
+        language.clone(),
+        "-f".into(),
+        input.to_string_lossy().into_owned(),
+    ];
+    args.extend(request.options.to_args());
+    if request.task == WhisperTask::Translate {
+        args.push("-tr".into());
+    }
+    Ok(args)
+}
+
+/// Tauri command: Run the bundled whisper CLI on an audio file. The request is typed and
+/// validated so the webview cannot pass arbitrary flags or paths: the model must be installed,
+/// the input must be a file the user picked or lie in the app data or temp directory, and no
+/// output files are written.
+///
+/// # Arguments
+/// * `app` - Tauri AppHandle
+/// * `request` - Input, model, language, task and decoding options
+///
+/// # Returns
+/// * `Ok(String)` - whisper-cli's console output, see `parse_whisper_output`
+/// * `Err(WhisperCliError)` - Why the request was rejected, or why whisper-cli failed
 #[tauri::command]
-pub fn run_whisper_cli(args: Vec<String>, app: AppHandle) -> Result<String, String> {
-    let bin_path = resolve_whisper_bin(&app)?;
-    run_whisper_cli_internal(&bin_path, &args, None)
+pub async fn run_whisper_cli(
+    app: AppHandle,
+    request: WhisperCliRequest,
+) -> Result<String, WhisperCliError> {
+    let args = whisper_cli_args(&app, &request)?;
+    let bin_path = resolve_whisper_bin(&app).map_err(WhisperCliError::Failed)?;
+    run_whisper_cli_internal(&bin_path, &args, None).map_err(WhisperCliError::Failed)
+}
+
+/// Settings for splitting long inputs at silences and transcribing the pieces in parallel.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ChunkingOptions {
+    /// Number of whisper-cli processes running at once; 1 transcribes the input in one piece.
+    pub workers: usize,
+    /// Target chunk length in milliseconds; chunks end at the silence closest to it.
+    pub chunk_ms: u64,
+}
+
+impl Default for ChunkingOptions {
+    fn default() -> Self {
+        ChunkingOptions {
+            workers: 1,
+            chunk_ms: 10 * 60 * 1000,
+        }
+    }
+}
+
+/// Optional settings for a transcription run.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TranscriptionOptions {
+    /// Fill in per-word timings, using DTW alignment when the model supports it.
+    pub word_timestamps: bool,
+    /// Audio track indices (`0:a:N`, see `probe_media`) to transcribe instead of the default.
+    pub audio_tracks: Vec<u32>,
+    /// Selects the audio tracks tagged with this language when `audio_tracks` is empty.
+    pub audio_language: Option<String>,
+    /// Mix all selected tracks into one; otherwise only the first selected track is used.
+    pub mix_audio_tracks: bool,
+    /// Only transcribe from this time on, in milliseconds.
+    pub start_ms: Option<u64>,
+    /// Only transcribe up to this time, in milliseconds.
+    pub end_ms: Option<u64>,
+    /// Preprocessing applied to the audio before it reaches whisper.
+    pub filters: AudioFilters,
+    /// Parallel transcription of long inputs.
+    pub chunking: ChunkingOptions,
+    /// `translate` adds an English translation pass after the transcription.
+    pub task: WhisperTask,
+    /// Decoding settings, e.g. loaded from a saved decoding preset.
+    pub decoding: WhisperOptions,
+    /// Name of a saved glossary whose terms are added to the prompt and whose corrections
+    /// are applied to the text.
+    pub glossary: Option<String>,
+    /// Skip non-speech audio before inference.
+    pub vad: VadOptions,
+    /// With language `auto`, detect the language on the first 30 seconds before the full
+    /// run and transcribe everything in it. Parallel runs always do this so their chunks
+    /// agree on one language.
+    pub detect_language: bool,
+}
+
+/// Runs a complete transcription job: registers it, runs the pipeline and reports its
+/// terminal status. Shared by `transcribe_file` and the batch queue.
+pub fn transcribe_job(
+    app: &impl AppContext,
+    job_id: &str,
+    input_path: &str,
+    model: &str,
+    language: &str,
+    options: &TranscriptionOptions,
+) -> Result<Transcription, String> {
+    register_job(app, job_id)?;
+    let result = run_transcription(app, job_id, input_path, model, language, options);
+    finish_job(app, job_id, result)
 }
 
 /// Tauri command: Transcribe an audio file using whisper, converting to WAV if needed.
@@ -66,54 +821,985 @@ pub fn run_whisper_cli(args: Vec<String>, app: AppHandle) -> Result<String, Stri
 /// * `app` - Tauri AppHandle
 /// * `input_path` - Path to the input file
 /// * `model` - Model name
-/// * `language` - Language code
+/// * `language` - Language code, or `auto` to detect it
+/// * `options` - Optional transcription settings
+/// * `job_id` - Id attached to progress events and used by `cancel_transcription`,
+///   a new UUID is used when omitted
 ///
 /// # Returns
-/// * `Ok(String)` - Transcription output
-/// * `Err(String)` - Error message
+/// * `Ok(Transcription)` - Segments and tokens parsed from whisper's JSON output
+/// * `Err(String)` - Error message, or `cancelled` if the job was cancelled
 #[tauri::command]
 pub async fn transcribe_file(
     app: AppHandle,
     input_path: String,
     model: String,
     language: String,
-) -> Result<String, String> {
-    use std::path::Path;
-    let ext = Path::new(&input_path)
+    options: Option<TranscriptionOptions>,
+    job_id: Option<String>,
+) -> Result<Transcription, String> {
+    let job_id = job_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    transcribe_job(
+        &app,
+        &job_id,
+        &input_path,
+        &model,
+        &language,
+        &options.unwrap_or_default(),
+    )
+}
+
+/// Sample rate whisper.cpp expects its input in.
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Inspects a WAV header and returns whether whisper-cli can read the file as is
+/// (16 kHz mono 16-bit PCM). Other layouts need to go through ffmpeg first.
+///
+/// # Returns
+/// * `Ok(bool)` - Whether the file can be passed straight to whisper-cli
+/// * `Err(String)` - The file is not a readable WAV or uses an unsupported encoding
+fn wav_is_whisper_ready(input_path: &str) -> Result<bool, String> {
+    let spec = match hound::WavReader::open(input_path) {
+        Ok(reader) => reader.spec(),
+        Err(hound::Error::Unsupported) => {
+            return Err(format!(
+                "[whisper] Unsupported WAV encoding in {}: only PCM and IEEE float are supported",
+                input_path
+            ))
+        }
+        Err(e) => {
+            return Err(format!(
+                "[whisper] Failed to read WAV file {}: {}",
+                input_path, e
+            ))
+        }
+    };
+    Ok(spec.sample_rate == WHISPER_SAMPLE_RATE
+        && spec.channels == 1
+        && spec.bits_per_sample == 16
+        && spec.sample_format == hound::SampleFormat::Int)
+}
+
+/// Model and decoding settings shared by every whisper-cli process of a job.
+struct WhisperRun<'a> {
+    bin_path: PathBuf,
+    model_path: String,
+    language: String,
+    dtw_preset: Option<String>,
+    /// Decoding settings with the thread count already resolved.
+    options: WhisperOptions,
+    vad: &'a VadOptions,
+    /// Glossary corrections applied to streamed cues.
+    replacements: Option<&'a Replacements>,
+    /// Translate to English instead of transcribing (`-tr`).
+    translate: bool,
+    /// Index of this pass and the number of passes of the job, to split progress between them.
+    pass: u32,
+    passes: u32,
+}
+
+impl WhisperRun<'_> {
+    /// Builds the argument list for whisper-cli, writing full JSON output (which includes
+    /// token timestamps) to `output_base`.json.
+    fn args(&self, input: &str, output_base: &Path) -> Vec<String> {
+        let mut args = vec![
+            "-m".into(),
+            self.model_path.clone(),
+            "-l".into(),
+            self.language.clone(),
+        ];
+        args.extend(["-f".into(), input.into()]);
+        args.extend(["-pp".into(), "-ojf".into(), "-of".into()]);
+        args.push(output_base.to_string_lossy().into_owned());
+        if let Some(preset) = &self.dtw_preset {
+            args.extend(["-dtw".into(), preset.clone()]);
+        }
+        args.extend(self.options.to_args());
+        if self.translate {
+            args.push("-tr".into());
+        }
+        args
+    }
+
+    /// Returns a progress callback that emits `transcription-progress` events for `job_id`,
+    /// scaled to this pass's share of the job.
+    fn progress_emitter<C: AppContext>(
+        &self,
+        app: &C,
+        job_id: &str,
+    ) -> impl Fn(u32) + Send + 'static {
+        let app = app.clone();
+        let job_id = job_id.to_string();
+        let (pass, passes) = (self.pass, self.passes.max(1));
+        move |percent| emit_progress(&app, &job_id, (pass * 100 + percent.min(100)) / passes)
+    }
+
+    /// Runs whisper-cli on `input` (a WAV path, or `-` with `stdin`) and moves the result
+    /// onto the input's timeline with `map_time`.
+    fn run(
+        &self,
+        app: &impl AppContext,
+        job_id: &str,
+        input: &str,
+        stdin: Option<Stdio>,
+        map_time: &impl Fn(u64) -> u64,
+        progress: impl Fn(u32) + Send + 'static,
+    ) -> Result<Transcription, String> {
+        let output_base = temp_output_base();
+        track_temp_file(job_id, output_base.with_extension("json"));
+        let args = self.args(input, &output_base);
+        let on_cue = |mut cue: Cue| {
+            cue.map_times(map_time);
+            if let Some(replacements) = self.replacements {
+                cue.apply_replacements(replacements);
+            }
+            app.emit_event(
+                "transcription-segment",
+                TranscriptionSegment {
+                    job_id: job_id.to_string(),
+                    cue,
+                    translation: self.translate,
+                },
+            );
+        };
+        let log = run_whisper_streaming(job_id, &self.bin_path, &args, stdin, on_cue, progress)?;
+        let mut transcription = read_whisper_json(&output_base)?;
+        transcription.map_times(map_time);
+        if self.language == AUTO_LANGUAGE {
+            if let Some((language, probability)) = parse_detected_language(&log) {
+                transcription.language = Some(language);
+                transcription.language_probability = Some(probability);
+            }
+        }
+        Ok(transcription)
+    }
+
+    /// Runs a detection-only pass (`-dl`) over the first 30 seconds of the input, either a WAV
+    /// whisper-cli reads directly or `conversion` piped through ffmpeg.
+    ///
+    /// # Returns
+    /// * `Ok((String, f32))` - The detected language code and its probability
+    /// * `Err(String)` - whisper-cli failed or reported no language
+    fn detect_language(
+        &self,
+        app: &impl AppContext,
+        job_id: &str,
+        input_path: &str,
+        conversion: Option<&WavConversion>,
+    ) -> Result<(String, f32), String> {
+        let mut args: Vec<String> = vec![
+            "-m".into(),
+            self.model_path.clone(),
+            "-l".into(),
+            AUTO_LANGUAGE.into(),
+            "-dl".into(),
+            "-d".into(),
+            DETECTION_MS.to_string(),
+        ];
+        if let Some(threads) = self.options.threads {
+            args.extend(["-t".into(), threads.to_string()]);
+        }
+        let log = match conversion {
+            Some(conversion) => {
+                let start_ms = conversion.start_ms.unwrap_or(0);
+                let end_ms = conversion.end_ms.map_or(start_ms + DETECTION_MS, |end| {
+                    end.min(start_ms + DETECTION_MS)
+                });
+                let sample = WavConversion {
+                    end_ms: Some(end_ms),
+                    ..conversion.clone()
+                };
+                let mut ffmpeg = spawn_ffmpeg_to_wav(app, input_path, &sample)?;
+                let stdin = ffmpeg.stdout.take().map(Stdio::from);
+                let ffmpeg = track_process(job_id, ffmpeg);
+                args.extend(["-f".into(), "-".into()]);
+                let log =
+                    run_whisper_streaming(job_id, &self.bin_path, &args, stdin, |_| {}, |_| {});
+                let _ = ffmpeg.lock().unwrap().wait();
+                log?
+            }
+            None => {
+                args.extend(["-f".into(), input_path.into()]);
+                run_whisper_streaming(job_id, &self.bin_path, &args, None, |_| {}, |_| {})?
+            }
+        };
+        parse_detected_language(&log)
+            .ok_or_else(|| "[whisper] whisper did not report a detected language".to_string())
+    }
+
+    /// Runs whisper-cli on a WAV file it can read directly.
+    fn transcribe_file(
+        &self,
+        app: &impl AppContext,
+        job_id: &str,
+        input_path: &str,
+    ) -> Result<Transcription, String> {
+        let progress = self.progress_emitter(app, job_id);
+        self.run(app, job_id, input_path, None, &|t| t, progress)
+    }
+
+    /// Converts the input with ffmpeg, pipes it to whisper-cli and shifts the result onto
+    /// the input's timeline.
+    fn transcribe_converted(
+        &self,
+        app: &impl AppContext,
+        job_id: &str,
+        input_path: &str,
+        conversion: &WavConversion,
+        progress: impl Fn(u32) + Send + 'static,
+    ) -> Result<Transcription, String> {
+        if self.vad.enabled {
+            return self.transcribe_speech(app, job_id, input_path, conversion, progress);
+        }
+        let offset_ms = conversion.start_ms.unwrap_or(0);
+        let mut ffmpeg = spawn_ffmpeg_to_wav(app, input_path, conversion)?;
+        let stdin = ffmpeg.stdout.take().map(Stdio::from);
+        let ffmpeg = track_process(job_id, ffmpeg);
+        let result = self.run(app, job_id, "-", stdin, &|t| t + offset_ms, progress);
+        let _ = ffmpeg.lock().unwrap().wait();
+        result
+    }
+
+    /// Decodes the input to a file, cuts out everything but speech and transcribes the rest,
+    /// mapping timestamps back onto the input's timeline.
+    fn transcribe_speech(
+        &self,
+        app: &impl AppContext,
+        job_id: &str,
+        input_path: &str,
+        conversion: &WavConversion,
+        progress: impl Fn(u32) + Send + 'static,
+    ) -> Result<Transcription, String> {
+        let offset_ms = conversion.start_ms.unwrap_or(0);
+        let decoded = temp_output_base().with_extension("wav");
+        let speech = temp_output_base().with_extension("wav");
+        track_temp_file(job_id, decoded.clone());
+        track_temp_file(job_id, speech.clone());
+        convert_to_wav_file(app, job_id, input_path, conversion, &decoded)?;
+        let map = compact_speech(&decoded, &speech, self.vad);
+        let _ = std::fs::remove_file(&decoded);
+        let map = map?;
+        if map.is_empty() {
+            progress(100);
+            return Ok(Transcription {
+                language: None,
+                language_probability: None,
+                segments: Vec::new(),
+                translation: None,
+            });
+        }
+        let result = self.run(
+            app,
+            job_id,
+            &speech.to_string_lossy(),
+            None,
+            &|t| offset_ms + map.to_original(t),
+            progress,
+        );
+        let _ = std::fs::remove_file(&speech);
+        result
+    }
+}
+
+/// Splits `0..total_ms` into chunks of roughly `target_ms`, cutting in the middle of the
+/// silence closest to each target point. Falls back to a hard cut where there is no silence
+/// within half a chunk of the target.
+fn plan_chunks(total_ms: u64, silences: &[(u64, u64)], target_ms: u64) -> Vec<(u64, u64)> {
+    let target_ms = target_ms.max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    // Let the last chunk grow up to 1.5x the target instead of leaving a tiny remainder
+    while total_ms - start > target_ms + target_ms / 2 {
+        let ideal = start + target_ms;
+        let cut = silences
+            .iter()
+            .map(|(s, e)| (s + e) / 2)
+            .filter(|mid| mid.abs_diff(ideal) <= target_ms / 2)
+            .min_by_key(|mid| mid.abs_diff(ideal))
+            .unwrap_or(ideal);
+        chunks.push((start, cut));
+        start = cut;
+    }
+    chunks.push((start, total_ms));
+    chunks
+}
+
+/// Transcribes the chunks of `conversion`'s range on a pool of `workers` threads and stitches
+/// the results. Overall progress is the chunks' progress weighted by their length.
+fn transcribe_chunks(
+    app: &impl AppContext,
+    job_id: &str,
+    input_path: &str,
+    conversion: &WavConversion,
+    whisper: &WhisperRun,
+    chunks: &[(u64, u64)],
+    workers: usize,
+) -> Result<Transcription, String> {
+    let range_start = conversion.start_ms.unwrap_or(0);
+    let weights: Vec<u64> = chunks.iter().map(|(s, e)| e - s).collect();
+    let total_weight = weights.iter().sum::<u64>().max(1);
+    let percents = Arc::new(Mutex::new(vec![0u32; chunks.len()]));
+    let last_percent = Arc::new(Mutex::new(0u32));
+    let results: Mutex<Vec<Option<Result<Transcription, String>>>> =
+        Mutex::new(chunks.iter().map(|_| None).collect());
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        for _ in 0..workers.min(chunks.len()) {
+            scope.spawn(|| loop {
+                if failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(&(chunk_start, chunk_end)) = chunks.get(index) else {
+                    break;
+                };
+                let chunk = WavConversion {
+                    start_ms: Some(range_start + chunk_start),
+                    end_ms: Some(range_start + chunk_end),
+                    ..conversion.clone()
+                };
+                let emit = whisper.progress_emitter(app, job_id);
+                let percents = percents.clone();
+                let last_percent = last_percent.clone();
+                let weights = weights.clone();
+                let progress = move |percent: u32| {
+                    let mut percents = percents.lock().unwrap();
+                    percents[index] = percent.min(100);
+                    let done: u64 = percents
+                        .iter()
+                        .zip(&weights)
+                        .map(|(p, w)| *p as u64 * w)
+                        .sum();
+                    let overall = (done / total_weight) as u32;
+                    let mut last = last_percent.lock().unwrap();
+                    if overall != *last {
+                        *last = overall;
+                        emit(overall);
+                    }
+                };
+                let result =
+                    whisper.transcribe_converted(app, job_id, input_path, &chunk, progress);
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    let mut transcriptions = Vec::new();
+    for (result, (_, chunk_end)) in results.into_inner().unwrap().into_iter().zip(chunks) {
+        match result {
+            Some(result) => transcriptions.push((range_start + chunk_end, result?)),
+            None => return Err("[whisper] Chunk transcription was interrupted".to_string()),
+        }
+    }
+    Ok(Transcription::stitch(transcriptions))
+}
+
+/// Runs the ffmpeg/whisper pipeline for a registered job.
+fn run_transcription(
+    app: &impl AppContext,
+    job_id: &str,
+    input_path: &str,
+    model: &str,
+    language: &str,
+    options: &TranscriptionOptions,
+) -> Result<Transcription, String> {
+    let is_wav = Path::new(input_path)
         .extension()
         .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    let is_audio = matches!(ext.as_str(), "wav" | "flac" | "mp3" | "ogg" | "m4a");
-    let bin_path = resolve_whisper_bin(&app)?;
-    let model_path = get_models_dir(&app).join(&model);
-    let model_path_str = model_path.to_string_lossy();
-    if is_audio && ext == "wav" {
-        let args = build_whisper_args(&model_path_str, &language, &input_path, false);
-        run_whisper_cli_internal(&bin_path, &args, None)
+        .is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+    let start_ms = options.start_ms.unwrap_or(0);
+    if options.end_ms.is_some_and(|end| end <= start_ms) {
+        return Err("[whisper] The end of the time range must be after its start".to_string());
+    }
+    options
+        .filters
+        .validate()
+        .map_err(|e| format!("[whisper] {}", e))?;
+    options
+        .decoding
+        .validate()
+        .map_err(|e| format!("[whisper] {}", e))?;
+    let glossary = match &options.glossary {
+        Some(name) => Some(get_glossary(app, name)?),
+        None => None,
+    };
+    let replacements = glossary.as_ref().map(|g| g.replacements()).transpose()?;
+    // Glossary terms follow the user's own prompt, if any
+    let prompt = [
+        options.decoding.prompt.as_deref().map(str::trim),
+        glossary.as_ref().and_then(|g| g.prompt()).as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|p| !p.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
+    let chunking = &options.chunking;
+    let parallel = chunking.workers > 1;
+    // Whisper decodes 30 second windows, shorter chunks only add overhead
+    if parallel && chunking.chunk_ms < 30_000 {
+        return Err("[whisper] Chunks must be at least 30 seconds long".to_string());
+    }
+    let whole_file = start_ms == 0 && options.end_ms.is_none();
+    let track_selected = !options.audio_tracks.is_empty() || options.audio_language.is_some();
+    let filtered = options.filters.to_filter_chain().is_some();
+    // Anything beyond a plain conversion needs ffmpeg, even for WAVs whisper could read
+    let direct_input = is_wav
+        && whole_file
+        && !track_selected
+        && !filtered
+        && !parallel
+        && !options.vad.enabled
+        && wav_is_whisper_ready(input_path)?;
+    let threads = options.decoding.threads.or_else(|| {
+        parallel.then(|| {
+            let cores = std::thread::available_parallelism().map_or(4, |n| n.get());
+            (cores / chunking.workers).max(1) as u32
+        })
+    });
+    let detect_first = language == AUTO_LANGUAGE && (options.detect_language || parallel);
+    let translate = options.task == WhisperTask::Translate;
+
+    // The conversion and chunk plan are shared by the transcription and translation passes
+    let (conversion, chunks) = if direct_input {
+        (None, Vec::new())
     } else {
-        // Convert to WAV using ffmpeg and pipe to whisper
-        let mut ffmpeg = spawn_ffmpeg_to_wav(&app, &input_path)?;
-        let args = build_whisper_args(&model_path_str, &language, "-", true);
-        let mut whisper = Command::new(&bin_path)
-            .args(&args)
-            .stdin(ffmpeg.stdout.take().unwrap())
-            .stdout(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("[whisper] Failed to start whisper CLI: {}", e))?;
-        let mut output = String::new();
-        if let Some(mut out) = whisper.stdout.take() {
-            if let Err(e) = out.read_to_string(&mut output) {
-                return Err(format!("[whisper] Failed to read whisper output: {}", e));
-            }
-        }
-        let status = whisper
-            .wait()
-            .map_err(|e| format!("[whisper] Failed to wait for whisper: {}", e))?;
-        if status.success() {
-            Ok(output)
+        let info = probe(app, input_path)?;
+        if !info.has_audio() {
+            return Err(format!("[whisper] {} has no audio stream", input_path));
+        }
+        let mut audio_tracks = info
+            .select_audio_tracks(&options.audio_tracks, options.audio_language.as_deref())
+            .map_err(|e| format!("[whisper] {}", e))?;
+        if !options.mix_audio_tracks {
+            audio_tracks.truncate(1);
+        }
+        let conversion = WavConversion {
+            audio_tracks,
+            start_ms: options.start_ms,
+            end_ms: options.end_ms,
+            filters: options.filters.clone(),
+        };
+        let range_ms = options
+            .end_ms
+            .or(info.duration_ms)
+            .map(|end| end.saturating_sub(start_ms));
+        let chunks = match range_ms {
+            Some(range_ms) if parallel && range_ms > chunking.chunk_ms + chunking.chunk_ms / 2 => {
+                let silences = detect_silences(app, job_id, input_path, &conversion)?;
+                plan_chunks(range_ms, &silences, chunking.chunk_ms)
+            }
+            _ => Vec::new(),
+        };
+        (Some(conversion), chunks)
+    };
+    let transcribe = |whisper: &WhisperRun| match &conversion {
+        None => whisper.transcribe_file(app, job_id, input_path),
+        Some(conversion) if chunks.len() > 1 => transcribe_chunks(
+            app,
+            job_id,
+            input_path,
+            conversion,
+            whisper,
+            &chunks,
+            chunking.workers,
+        ),
+        Some(conversion) => {
+            let progress = whisper.progress_emitter(app, job_id);
+            whisper.transcribe_converted(app, job_id, input_path, conversion, progress)
+        }
+    };
+
+    let mut whisper = WhisperRun {
+        bin_path: resolve_whisper_bin(app)?,
+        model_path: get_models_dir(app)
+            .join(model)
+            .to_string_lossy()
+            .into_owned(),
+        language: language.to_string(),
+        dtw_preset: if options.word_timestamps {
+            dtw_preset_for_model(model)
         } else {
-            Err("whisper CLI failed".to_string())
+            None
+        },
+        options: WhisperOptions {
+            threads,
+            prompt: (!prompt.is_empty()).then_some(prompt),
+            ..options.decoding.clone()
+        },
+        vad: &options.vad,
+        replacements: replacements.as_ref(),
+        translate: false,
+        pass: 0,
+        passes: if translate { 2 } else { 1 },
+    };
+    let detected = if detect_first {
+        let detected = whisper.detect_language(app, job_id, input_path, conversion.as_ref())?;
+        whisper.language = detected.0.clone();
+        Some(detected)
+    } else {
+        None
+    };
+    let mut transcription = transcribe(&whisper)?;
+    if let Some((language, probability)) = detected {
+        transcription.language = Some(language);
+        transcription.language_probability = Some(probability);
+    }
+    if translate {
+        // Translate from the language the first pass settled on instead of detecting again
+        if let Some(language) = &transcription.language {
+            whisper.language = language.clone();
+        }
+        whisper.translate = true;
+        whisper.pass = 1;
+        transcription.translation = Some(transcribe(&whisper)?.segments);
+    }
+    emit_progress(app, job_id, 100);
+    if options.word_timestamps {
+        let translation = transcription.translation.iter_mut().flatten();
+        for segment in transcription.segments.iter_mut().chain(translation) {
+            segment.words = segment.build_words();
+        }
+    }
+    if let Some(replacements) = &replacements {
+        transcription.apply_replacements(replacements);
+    }
+    Ok(transcription)
+}
+
+/// Tauri command: Convert a transcription result into a subtitle document.
+///
+/// # Arguments
+/// * `transcription` - Result of `transcribe_file`
+/// * `text` - Original text (default), English translation or both as bilingual cues
+///
+/// # Returns
+/// * `Ok(SubtitleDocument)` - The subtitle document
+/// * `Err(String)` - Error message if the translation is missing
+#[tauri::command]
+pub fn transcription_to_document(
+    transcription: Transcription,
+    text: Option<TranscriptText>,
+) -> Result<SubtitleDocument, String> {
+    transcription.to_document(text.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::PlainContext;
+    use hound::{SampleFormat, WavSpec, WavWriter};
+
+    /// Returns a unique path in the temp dir with the given extension.
+    fn temp_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "captions-test-{}.{}",
+            uuid::Uuid::new_v4(),
+            extension
+        ))
+    }
+
+    /// Writes a short silent WAV fixture with the given layout.
+    fn write_wav(sample_rate: u32, channels: u16, float: bool) -> PathBuf {
+        let path = temp_path("wav");
+        let spec = WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: if float { 32 } else { 16 },
+            sample_format: if float {
+                SampleFormat::Float
+            } else {
+                SampleFormat::Int
+            },
+        };
+        let mut writer = WavWriter::create(&path, spec).unwrap();
+        for _ in 0..sample_rate / 10 * channels as u32 {
+            if float {
+                writer.write_sample(0.0f32).unwrap();
+            } else {
+                writer.write_sample(0i16).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    fn check(path: &Path) -> Result<bool, String> {
+        let result = wav_is_whisper_ready(&path.to_string_lossy());
+        let _ = std::fs::remove_file(path);
+        result
+    }
+
+    #[test]
+    fn mono_16khz_pcm_is_ready() {
+        assert_eq!(check(&write_wav(16000, 1, false)), Ok(true));
+    }
+
+    #[test]
+    fn stereo_44khz_needs_conversion() {
+        assert_eq!(check(&write_wav(44100, 2, false)), Ok(false));
+    }
+
+    #[test]
+    fn float_samples_need_conversion() {
+        assert_eq!(check(&write_wav(16000, 1, true)), Ok(false));
+    }
+
+    #[test]
+    fn non_pcm_encoding_is_an_error() {
+        // Header of a mono 16 kHz file in 8-bit A-law (format 0x0006)
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend(0x0006u16.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(16000u32.to_le_bytes());
+        bytes.extend(16000u32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(8u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend(4u32.to_le_bytes());
+        bytes.extend([0u8; 4]);
+        let path = temp_path("wav");
+        std::fs::write(&path, bytes).unwrap();
+        let error = check(&path).unwrap_err();
+        assert!(error.contains("Unsupported WAV encoding"), "{}", error);
+    }
+
+    #[test]
+    fn garbage_file_is_an_error() {
+        let path = temp_path("wav");
+        std::fs::write(&path, b"this is not a wav file").unwrap();
+        assert!(check(&path).is_err());
+    }
+
+    /// whisper-cli stand-in that records its arguments and writes a fixed `-ojf` result.
+    #[cfg(unix)]
+    const STUB_WHISPER: &str = r#"#!/bin/sh
+printf '%s\n' "$@" > "$(dirname "$0")/args"
+while [ $# -gt 0 ]; do
+    if [ "$1" = "-of" ]; then out="$2"; fi
+    shift
+done
+echo "[00:00:00.000 --> 00:00:01.500]   Hello world"
+cat > "$out.json" <<'JSON'
+{"result":{"language":"en"},"transcription":[{"offsets":{"from":0,"to":1500},"text":" Hello world",
+"tokens":[{"id":1,"text":" Hello","offsets":{"from":0,"to":700},"p":0.9,"t_dtw":-1},
+{"id":2,"text":" world","offsets":{"from":700,"to":1500},"p":0.8,"t_dtw":-1}]}]}
+JSON
+"#;
+
+    #[cfg(unix)]
+    #[test]
+    fn transcribe_job_runs_whisper_from_the_resource_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let app = PlainContext::temp();
+        let bin_dir = app.resource_dir.join("gen/whisper-bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        let bin_path = bin_dir.join("whisper");
+        std::fs::write(&bin_path, STUB_WHISPER).unwrap();
+        std::fs::set_permissions(&bin_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let input = write_wav(16000, 1, false);
+
+        let transcription = transcribe_job(
+            &app,
+            &uuid::Uuid::new_v4().to_string(),
+            input.to_str().unwrap(),
+            "ggml-base.bin",
+            "en",
+            &TranscriptionOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(transcription.language.as_deref(), Some("en"));
+        assert_eq!(transcription.segments.len(), 1);
+        let segment = &transcription.segments[0];
+        assert_eq!((segment.start, segment.end), (0, 1500));
+        assert_eq!(segment.text, " Hello world");
+        assert_eq!(segment.tokens.len(), 2);
+        let args = std::fs::read_to_string(bin_dir.join("args")).unwrap();
+        let args: Vec<&str> = args.lines().collect();
+        let model_path = get_models_dir(&app).join("ggml-base.bin");
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["-m", model_path.to_str().unwrap()]));
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["-f", input.to_str().unwrap()]));
+    }
+
+    /// Creates a temp-dir context with an installed `ggml-base.bin` model.
+    fn context_with_model() -> PlainContext {
+        let app = PlainContext::temp();
+        let models_dir = get_models_dir(&app);
+        std::fs::create_dir_all(&models_dir).unwrap();
+        std::fs::write(models_dir.join("ggml-base.bin"), b"model").unwrap();
+        app
+    }
+
+    fn cli_request(input_path: &Path, model: &str) -> WhisperCliRequest {
+        WhisperCliRequest {
+            input_path: input_path.to_string_lossy().into_owned(),
+            model: model.to_string(),
+            language: "en".to_string(),
+            task: WhisperTask::Transcribe,
+            options: WhisperOptions::default(),
+        }
+    }
+
+    #[test]
+    fn whisper_cli_accepts_inputs_in_the_data_dir() {
+        let app = context_with_model();
+        let input = app.data_dir.join("talk.wav");
+        std::fs::write(&input, b"audio").unwrap();
+        let args = whisper_cli_args(&app, &cli_request(&input, "ggml-base.bin")).unwrap();
+        let input = std::fs::canonicalize(&input).unwrap();
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["-f", input.to_str().unwrap()]));
+    }
+
+    #[test]
+    fn whisper_cli_rejects_model_paths() {
+        let app = context_with_model();
+        let input = write_wav(16000, 1, false);
+        for model in ["../x.bin", "models/ggml-base.bin", "ggml-missing.bin"] {
+            assert_eq!(
+                whisper_cli_args(&app, &cli_request(&input, model)),
+                Err(WhisperCliError::UnknownModel(model.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn whisper_cli_rejects_hidden_directories() {
+        let app = context_with_model();
+        let hidden = app.data_dir.join(".secrets");
+        std::fs::create_dir_all(&hidden).unwrap();
+        let input = hidden.join("talk.wav");
+        std::fs::write(&input, b"audio").unwrap();
+        assert!(matches!(
+            permitted_input(&app, input.to_str().unwrap()),
+            Err(WhisperCliError::PathNotPermitted(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn whisper_cli_rejects_paths_leaving_the_permitted_dirs() {
+        let app = context_with_model();
+        let traversal = app.data_dir.join("../../../../../../../../etc/hosts");
+        assert!(matches!(
+            permitted_input(&app, traversal.to_str().unwrap()),
+            Err(WhisperCliError::PathNotPermitted(_))
+        ));
+        let link = app.data_dir.join("hosts.wav");
+        std::os::unix::fs::symlink("/etc/hosts", &link).unwrap();
+        assert!(matches!(
+            permitted_input(&app, link.to_str().unwrap()),
+            Err(WhisperCliError::PathNotPermitted(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn whisper_cli_accepts_files_the_user_picked() {
+        let mut app = context_with_model();
+        let picked = std::fs::canonicalize("/etc/hosts").unwrap();
+        app.selected_paths.push(picked.clone());
+        assert_eq!(permitted_input(&app, "/etc/hosts").unwrap(), picked);
+    }
+
+    const MINUTE: u64 = 60_000;
+
+    #[test]
+    fn chunks_are_cut_hard_without_silences() {
+        assert_eq!(
+            plan_chunks(25 * MINUTE, &[], 10 * MINUTE),
+            [(0, 10 * MINUTE), (10 * MINUTE, 25 * MINUTE)]
+        );
+    }
+
+    #[test]
+    fn chunks_are_cut_in_the_silence_closest_to_the_target() {
+        let silences = [
+            (6 * MINUTE, 6 * MINUTE + 2000),
+            (10 * MINUTE + 1000, 10 * MINUTE + 3000),
+        ];
+        assert_eq!(
+            plan_chunks(25 * MINUTE, &silences, 10 * MINUTE),
+            [(0, 10 * MINUTE + 2000), (10 * MINUTE + 2000, 25 * MINUTE)]
+        );
+        // Silences more than half a chunk away from the target are ignored
+        let far = [(2 * MINUTE, 2 * MINUTE + 2000)];
+        assert_eq!(
+            plan_chunks(25 * MINUTE, &far, 10 * MINUTE)[0],
+            (0, 10 * MINUTE)
+        );
+    }
+
+    #[test]
+    fn short_inputs_are_one_chunk() {
+        assert_eq!(plan_chunks(4 * MINUTE, &[], 10 * MINUTE), [(0, 4 * MINUTE)]);
+        assert_eq!(
+            plan_chunks(14 * MINUTE, &[], 10 * MINUTE),
+            [(0, 14 * MINUTE)]
+        );
+    }
+
+    fn segment(start: u64, end: u64, text: &str) -> Segment {
+        Segment {
+            start,
+            end,
+            text: text.to_string(),
+            tokens: Vec::new(),
+            words: vec![Word {
+                start,
+                end,
+                text: text.to_string(),
+                confidence: None,
+            }],
+        }
+    }
+
+    fn chunk(language: &str, segments: Vec<Segment>) -> Transcription {
+        Transcription {
+            language: Some(language.to_string()),
+            language_probability: None,
+            segments,
+            translation: None,
+        }
+    }
+
+    #[test]
+    fn stitch_joins_chunks_on_the_full_timeline() {
+        let first = chunk(
+            "en",
+            vec![segment(0, 4000, "one"), segment(4000, 9500, "two")],
+        );
+        let mut second = chunk(
+            "de",
+            vec![segment(500, 3000, "three"), segment(9000, 9800, "four")],
+        );
+        // The second chunk starts at 9 s of the input
+        second.map_times(|t| t + 9000);
+        let stitched = Transcription::stitch(vec![(9000, first), (12_000, second)]);
+
+        assert_eq!(stitched.language.as_deref(), Some("en"));
+        let times: Vec<(u64, u64)> = stitched.segments.iter().map(|s| (s.start, s.end)).collect();
+        // `two` is clamped to its chunk and `four` starts past the end of its chunk
+        assert_eq!(times, [(0, 4000), (4000, 9000), (9500, 12_000)]);
+        assert_eq!(stitched.segments[1].words[0].end, 9000);
+        assert_eq!(stitched.segments[2].words[0].start, 9500);
+    }
+
+    #[test]
+    fn detected_language_is_read_from_the_log() {
+        let log =
+            "whisper_init_from_file_with_params_no_state: loading model from 'ggml-base.bin'\n\
+                   whisper_full_with_state: auto-detected language: de (p = 0.973215)\n\
+                   whisper_print_timings:     load time =    92.31 ms\n";
+        assert_eq!(
+            parse_detected_language(log),
+            Some(("de".to_string(), 0.973215))
+        );
+    }
+
+    #[test]
+    fn log_without_detection_has_no_language() {
+        let log = "whisper_full_with_state: progress =  50%\n\
+                   whisper_print_timings:    total time =  1021.54 ms\n";
+        assert_eq!(parse_detected_language(log), None);
+    }
+
+    #[test]
+    fn default_options_are_valid_and_add_no_flags() {
+        let options = WhisperOptions::default();
+        assert_eq!(options.validate(), Ok(()));
+        assert!(options.to_args().is_empty());
+    }
+
+    #[test]
+    fn out_of_range_options_are_rejected() {
+        let cases = [
+            (
+                WhisperOptions {
+                    temperature: Some(1.5),
+                    ..WhisperOptions::default()
+                },
+                "Temperature must be between 0 and 1",
+            ),
+            (
+                WhisperOptions {
+                    temperature: Some(-0.1),
+                    ..WhisperOptions::default()
+                },
+                "Temperature must be between 0 and 1",
+            ),
+            (
+                WhisperOptions {
+                    logprob_threshold: Some(0.5),
+                    ..WhisperOptions::default()
+                },
+                "Log probability threshold must be 0 or below",
+            ),
+            (
+                WhisperOptions {
+                    logprob_threshold: Some(f32::NAN),
+                    ..WhisperOptions::default()
+                },
+                "Log probability threshold must be 0 or below",
+            ),
+            (
+                WhisperOptions {
+                    threads: Some(0),
+                    ..WhisperOptions::default()
+                },
+                "Thread count must be at least 1",
+            ),
+        ];
+        for (options, message) in cases {
+            assert_eq!(options.validate(), Err(message.to_string()));
         }
     }
+
+    #[test]
+    fn options_map_to_flag_pairs() {
+        let options = WhisperOptions {
+            beam_size: Some(5),
+            entropy_threshold: Some(2.4),
+            logprob_threshold: Some(-1.0),
+            threads: Some(4),
+            prompt: Some("  Kubernetes, Ansible ".to_string()),
+            suppress_non_speech: true,
+            ..WhisperOptions::default()
+        };
+        assert_eq!(options.validate(), Ok(()));
+        let args = options.to_args();
+        let pair = |flag: &str| {
+            let i = args.iter().position(|a| a == flag)?;
+            args.get(i + 1).map(String::as_str)
+        };
+        assert_eq!(pair("-bs"), Some("5"));
+        assert_eq!(pair("-et"), Some("2.4"));
+        assert_eq!(pair("-lpt"), Some("-1"));
+        assert_eq!(pair("-t"), Some("4"));
+        assert_eq!(pair("--prompt"), Some("Kubernetes, Ansible"));
+        assert!(args.iter().any(|a| a == "-sns"));
+        assert!(!args.iter().any(|a| a == "-nf" || a == "-sow"));
+    }
+
+    #[test]
+    fn blank_prompt_is_omitted() {
+        let options = WhisperOptions {
+            prompt: Some("   ".to_string()),
+            ..WhisperOptions::default()
+        };
+        assert!(options.to_args().is_empty());
+    }
 }
@@ -9,7 +9,7 @@ import { message } from '@tauri-apps/plugin-dialog';
  */
 export function useTranscription() {
     const [filePath, setFilePath] = useState<string | null>(null);
-    const [language, setLanguage] = useState<string>("en"); // Default language
+    const [language, setLanguage] = useState<string>("auto"); // Default language
     const {
         models,
         loading: modelsLoading,
@@ -42,11 +42,13 @@ export function useWhisper() {
         }
         setLoading(true);
         try {
-            const result = await invoke<string>("transcribe_file", {
+            const transcription = await invoke<unknown>("transcribe_file", {
                 inputPath: args.audio.path,
                 model: args.model,
                 language: args.language,
             });
+            const document = await invoke<unknown>("transcription_to_document", { transcription });
+            const result = await invoke<string>("document_to_srt", { document });
             setOutput(prev => (prev !== result ? result : prev));
         } catch (err: any) {
             setError(err?.toString() || "Failed to run whisper");
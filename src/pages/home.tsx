@@ -4,6 +4,7 @@ import { useTranscription } from "@/hooks/useTranscription";
 
 // Whisper supported languages
 const WHISPER_LANGUAGES = [
+    { code: "auto", name: "Detect automatically" },
     { code: "en", name: "English" },
     { code: "es", name: "Spanish" },
     { code: "fr", name: "French" },